@@ -27,6 +27,12 @@ pub(crate) mod bindings{
     ::windows::include_bindings!();
 }
 
+/*
+    Unix Specific Crates
+ */
+#[cfg(unix)]
+extern crate libc;
+
 /*
     Mac Specific Crates
  */
@@ -168,20 +174,324 @@ mod tests {
         let pid : u32 = 1818;
         println!("{:?}", is_process_running(&pid));
 
-        let time = FileTime {
-            day: 13,
-            month: 3,
-            year: 2022,
-            hour: 2,
-            minute: 46,
-            second: 46,
-            milliseconds: 0
-        };
+        let time = FileTime::builder()
+            .set_day(13)
+            .set_month(1)
+            .set_year(2022)
+            .set_hour(2)
+            .set_minute(46)
+            .set_second(46)
+            .set_millisecond(0)
+            .build()
+            .unwrap();
         let systime = filetime_to_systime(&time);
-        assert_eq!(systime,"202203130246.46");
-        set_creation_date(Path::new("./test.txt"), &time);
-        set_changed_date( Path::new("./test.txt"), &time);
-        set_accessed_date( Path::new("./test.txt"), &time);
+        assert_eq!(systime,"202201130246.46");
+        if cfg!(target_os = "macos") {
+            set_creation_date(Path::new("./test.txt"), &time).unwrap();
+        } else {
+            assert_eq!(set_creation_date(Path::new("./test.txt"), &time).unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+        }
+        set_changed_date( Path::new("./test.txt"), &time).unwrap();
+        set_accessed_date( Path::new("./test.txt"), &time).unwrap();
+    }
+    #[test]
+    fn set_dates_nanoseconds() {
+        let path = std::env::temp_dir().join("se_set_dates_nanoseconds.test");
+        File::create(&path).unwrap();
+
+        let time: FileTime = "2022-01-13T02:46:46.123456789".parse().unwrap();
+        set_changed_date(&path, &time).expect("Unable to set the modified date");
+        set_accessed_date(&path, &time).expect("Unable to set the accessed date");
+
+        let meta = std::fs::metadata(&path).unwrap();
+        let modified = meta.modified().unwrap().duration_since(std::time::UNIX_EPOCH).unwrap();
+        let accessed = meta.accessed().unwrap().duration_since(std::time::UNIX_EPOCH).unwrap();
+        assert_eq!(modified.subsec_nanos(), 123_456_789);
+        assert_eq!(modified, accessed);
+
+        assert!(set_changed_date(Path::new("./does_not_exist.test"), &time).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn get_dates() {
+        use crate::metadata::time::{get_modified_date, get_accessed_date, get_changed_date, get_creation_date};
+
+        let path = std::env::temp_dir().join("se_get_dates.test");
+        File::create(&path).unwrap();
+
+        let time = FileTime::builder().set_day(24).set_month(12).set_year(2021)
+            .set_hour(18).set_minute(30).set_second(5).set_nanosecond(5_000_100)
+            .build().unwrap();
+        set_changed_date(&path, &time).unwrap();
+
+        let modified = get_modified_date(&path).unwrap();
+        assert_eq!(modified.to_system_time().unwrap(), time.to_system_time().unwrap());
+        assert_eq!((modified.hour(), modified.minute(), modified.nanosecond()), (Some(18), Some(30), Some(5_000_100)));
+        assert!(get_accessed_date(&path).is_ok());
+        assert!(get_changed_date(&path).is_ok());
+        match get_creation_date(&path) {
+            Ok(_) => {}
+            Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::Unsupported)
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn get_dates_dst_overlap() {
+        // Changing TZ would affect the other tests, so the check runs in a child process.
+        let status = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "tests::dst_overlap_round_trip", "--ignored", "--quiet"])
+            .env("TZ", "America/New_York")
+            .status().unwrap();
+        assert!(status.success());
+    }
+    #[test]
+    #[ignore = "run by get_dates_dst_overlap with TZ set"]
+    fn dst_overlap_round_trip() {
+        use crate::metadata::time::get_modified_date;
+        use std::convert::TryFrom;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        // 2021-11-07 01:30 happens twice in New York, this is the second time. (EST)
+        let instant = UNIX_EPOCH + Duration::from_secs(1636266600);
+        let path = std::env::temp_dir().join("se_dst_overlap.test");
+        File::create(&path).unwrap();
+        set_changed_date(&path, &"2021-11-07T06:30:00Z".parse().unwrap()).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), instant);
+
+        let modified = get_modified_date(&path).unwrap();
+        assert_eq!(modified.to_system_time().unwrap(), instant);
+        set_changed_date(&path, &modified).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), instant);
+        set_changed_date(&path, &crate::metadata::inspect(&path).unwrap().modified).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), instant);
+        std::fs::remove_file(&path).unwrap();
+
+        let converted = FileTime::try_from(instant).unwrap();
+        assert_eq!(converted.to_system_time().unwrap(), instant);
+    }
+    #[test]
+    fn set_dates_nofollow() {
+        use crate::metadata::time::{set_changed_date_nofollow, set_accessed_date_nofollow};
+
+        let target = std::env::temp_dir().join("se_nofollow_target.test");
+        let link = std::env::temp_dir().join("se_nofollow_link.test");
+        File::create(&target).unwrap();
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let time: FileTime = "2001-02-03T04:05:06.7Z".parse().unwrap();
+        set_changed_date_nofollow(&link, &time).unwrap();
+        set_accessed_date_nofollow(&link, &time).unwrap();
+
+        let link_modified = std::fs::symlink_metadata(&link).unwrap().modified().unwrap();
+        let target_modified = std::fs::metadata(&target).unwrap().modified().unwrap();
+        assert_eq!(link_modified, time.to_system_time().unwrap());
+        assert_ne!(target_modified, link_modified);
+
+        std::fs::remove_file(&link).unwrap();
+        std::fs::remove_file(&target).unwrap();
+    }
+    #[test]
+    fn set_times_on_file() {
+        use crate::metadata::time::{set_times, Times};
+
+        let path = std::env::temp_dir().join("se_set_times.test");
+        let mut file = File::create(&path).unwrap();
+        file.write_all(b"Howdy from System-extensions").unwrap();
+
+        let time: FileTime = "2010-05-06T07:08:09.123456789Z".parse().unwrap();
+        set_times(&file, Times { accessed: Some(time), modified: Some(time), created: None }).unwrap();
+        assert!(set_times(&file, Times { created: Some(time), ..Times::default() }).is_err());
+        drop(file);
+
+        let meta = std::fs::metadata(&path).unwrap();
+        assert_eq!(meta.modified().unwrap(), time.to_system_time().unwrap());
+        assert_eq!(meta.accessed().unwrap(), time.to_system_time().unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn normalize_tree() {
+        use crate::metadata::time::{normalize_tree, glob_matches, NormalizeOptions, NormalizeMode};
+        use std::fs;
+
+        assert!(glob_matches("*.rs", "lib.rs", "src/lib.rs"));
+        assert!(!glob_matches("src/*.rs", "lib.rs", "src/metadata/time.rs"));
+        assert!(glob_matches("src/**/*.rs", "time.rs", "src/metadata/time.rs"));
+        assert!(glob_matches("src/**/*.rs", "lib.rs", "src/lib.rs"));
+        assert!(glob_matches("?.txt", "a.txt", "a.txt"));
+        assert!(glob_matches("**/b", "b", "b") && glob_matches("**/b", "b", "a/a/b"));
+        assert!(!glob_matches("a/**/", "a", "a/b") && !glob_matches("*/b", "b", "a/a/b"));
+        // Many stars must not backtrack exponentially.
+        assert!(!glob_matches("**a**a**a**a**a**a**a**a**b/", "c", &("a/".repeat(200) + "c")));
+
+        let dir = std::env::temp_dir().join("se_normalize_tree");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        File::create(dir.join("src/lib.rs")).unwrap();
+        File::create(dir.join(".git/HEAD")).unwrap();
+
+        let time: FileTime = "2020-01-01T00:00:00Z".parse().unwrap();
+        let mut options = NormalizeOptions::new();
+        options.set_use_source_date_epoch(false).add_exclude(".git");
+        assert_eq!(normalize_tree(&dir, &time, &options).unwrap(), 3);
+
+        let target = time.to_system_time().unwrap();
+        assert_eq!(fs::metadata(dir.join("src/lib.rs")).unwrap().modified().unwrap(), target);
+        assert_ne!(fs::metadata(dir.join(".git/HEAD")).unwrap().modified().unwrap(), target);
+
+        // Older times are kept when clamping.
+        let older: FileTime = "2019-01-01T00:00:00Z".parse().unwrap();
+        set_changed_date(&dir.join("src/lib.rs"), &older).unwrap();
+        File::create(dir.join("src/new.rs")).unwrap();
+        options.set_mode(NormalizeMode::Clamp);
+        normalize_tree(&dir, &time, &options).unwrap();
+        assert_eq!(fs::metadata(dir.join("src/lib.rs")).unwrap().modified().unwrap(), older.to_system_time().unwrap());
+        assert_eq!(fs::metadata(dir.join("src/new.rs")).unwrap().modified().unwrap(), target);
+        assert_eq!(fs::metadata(dir.join("src")).unwrap().modified().unwrap(), target);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn shift_times() {
+        use crate::metadata::time::{shift_times, Which};
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join("se_shift_times.test");
+        File::create(&path).unwrap();
+        let time: FileTime = "2020-06-01T12:00:00.123456789Z".parse().unwrap();
+        set_changed_date(&path, &time).unwrap();
+        set_accessed_date(&path, &time).unwrap();
+
+        shift_times([&path], chrono::Duration::hours(2), Which::Modified).unwrap();
+        let meta = std::fs::metadata(&path).unwrap();
+        assert_eq!(meta.modified().unwrap(), time.to_system_time().unwrap() + Duration::from_secs(7200));
+        assert_eq!(meta.accessed().unwrap(), time.to_system_time().unwrap());
+
+        let missing = std::env::temp_dir().join("se_shift_times_missing.test");
+        let failures = shift_times(vec![&path, &missing], chrono::Duration::minutes(-30), Which::Both).unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, missing);
+        assert_eq!(std::fs::metadata(&path).unwrap().accessed().unwrap(), time.to_system_time().unwrap() - Duration::from_secs(1800));
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn preserve_times() {
+        use crate::metadata::time::PreserveTimes;
+
+        let path = std::env::temp_dir().join("se_preserve_times.test");
+        std::fs::write(&path, b"Howdy").unwrap();
+        let time: FileTime = "2015-07-08T09:10:11.987654321Z".parse().unwrap();
+        set_changed_date(&path, &time).unwrap();
+
+        let result = std::panic::catch_unwind(|| {
+            let _guard = PreserveTimes::new(&path).unwrap();
+            std::fs::write(&path, b"Howdy from System-extensions").unwrap();
+            panic!("The edit failed");
+        });
+        assert!(result.is_err());
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), time.to_system_time().unwrap());
+
+        let guard = PreserveTimes::new(&path).unwrap();
+        std::fs::write(&path, b"Howdy again").unwrap();
+        guard.forget();
+        assert_ne!(std::fs::metadata(&path).unwrap().modified().unwrap(), time.to_system_time().unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn file_time_conversions() {
+        use std::convert::TryFrom;
+        use std::time::SystemTime;
+
+        assert!(FileTime::new(30, 2, 2021).is_err());
+        assert!(FileTime::new(29, 2, 2021).is_err());
+        assert!(FileTime::new(29, 2, 2020).is_ok());
+        assert!(FileTime::new(1, 13, 2021).is_err());
+        assert!(FileTime::builder().set_month(2).set_day(29).build().is_ok());
+        assert!(FileTime::builder().set_hour(24).build().is_err());
+
+        let time = FileTime::parse_iso8601("2021-12-25T18:30:05.25").unwrap();
+        assert_eq!(time.to_string(), "2021-12-25T18:30:05.25");
+        assert_eq!(time.to_iso8601(), "2021-12-25T18:30:05.250");
+        assert_eq!(time.to_touch_format(), "202112251830.05");
+        assert_eq!(FileTime::parse_iso8601("2021-12-25").unwrap().to_string(), "2021-12-25");
+        assert_eq!(FileTime::builder().set_hour(3).build().unwrap().to_string(), "----------T03:--:--");
+        assert_eq!(FileTime::parse_rfc3339(&time.to_rfc3339()).unwrap().to_system_time().unwrap(),
+                   time.to_system_time().unwrap());
+        assert!(FileTime::parse_rfc3339("2021-12-25").is_err());
+        assert!("not a date".parse::<FileTime>().is_err());
+
+        let system = SystemTime::from(time);
+        let converted = FileTime::try_from(system).unwrap();
+        assert_eq!(SystemTime::from(converted), system);
+        assert_eq!((converted.day(), converted.hour(), converted.second()), (Some(25), Some(18), Some(5)));
+        let now = FileTime::now();
+        assert!(now.year().is_some() && now.nanosecond().is_some());
+    }
+    #[test]
+    fn file_time_zones() {
+        use crate::metadata::time::Zone;
+        use std::time::SystemTime;
+
+        let utc = FileTime::parse_iso8601("2021-12-25T18:30:00Z").unwrap();
+        let offset = FileTime::parse_rfc3339("2021-12-25T19:30:00+01:00").unwrap();
+        assert_eq!(utc.zone(), Zone::Utc);
+        assert_eq!(utc.to_string(), "2021-12-25T18:30:00Z");
+        assert_eq!(offset.to_string(), "2021-12-25T19:30:00+01:00");
+        assert_eq!(utc.to_rfc3339(), "2021-12-25T18:30:00Z");
+        assert_eq!(utc.to_system_time().unwrap(), offset.to_system_time().unwrap());
+        assert_eq!(SystemTime::from(utc), SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1640457000));
+
+        let built = FileTime::builder().set_day(25).set_month(12).set_year(2021).set_hour(18).set_minute(30)
+            .set_second(0).set_nanosecond(0).set_zone(Zone::Utc).build().unwrap();
+        assert_eq!(built, utc);
+        assert_eq!(FileTime::parse_iso8601("2021-12-25T18:30:00").unwrap().zone(), Zone::Local);
+    }
+    #[test]
+    fn metadata_capabilities() {
+        use crate::metadata::capabilities;
+        use crate::metadata::attribute::Attributes;
+
+        let caps = capabilities(&std::env::temp_dir()).unwrap();
+        assert!(!caps.filesystem_type.is_empty());
+        assert!(caps.timestamp_resolution > std::time::Duration::from_secs(0));
+        assert!(caps.attributes.contains(Attributes::READ_ONLY));
+        if cfg!(target_os = "linux") {
+            assert!(!caps.birth_time_settable);
+        }
+        // The attribute written to check for DOS attribute support is removed again.
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        {
+            let names = crate::metadata::xattr::list(&std::env::temp_dir()).unwrap_or_default();
+            assert!(!names.iter().any(|name| name == "user.system_extensions.probe"));
+        }
+        assert!(capabilities(Path::new("./does_not_exist.test")).is_err());
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn system_stats() {
+        let stats = crate::processes::processes::system_stats().expect("Unable to read system stats");
+        assert!(stats.pid_max > 0);
+        assert!(stats.total_processes >= stats.running_processes);
+        assert!(stats.forks_since_boot > 0);
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn process_monitor() {
+        use crate::processes::monitor::{ProcessMonitor, SortBy};
+        use std::time::Duration;
+
+        let top = ProcessMonitor::new()
+            .set_sort_by(SortBy::Rss)
+            .set_limit(3)
+            .set_interval(Duration::from_millis(50))
+            .sample()
+            .expect("Unable to sample processes");
+        assert!(!top.is_empty() && top.len() <= 3);
+        assert!(top.windows(2).all(|w| w[0].rss > w[1].rss || (w[0].rss == w[1].rss && w[0].pid < w[1].pid)));
+
+        let root = ProcessMonitor::new().set_user_name("root").set_interval(Duration::from_millis(50)).sample().unwrap();
+        assert!(root.iter().all(|process| process.uid == 0));
+        assert!(ProcessMonitor::new().set_user_name("se-unknown-user").sample().is_err());
     }
     #[test]
     fn dialog_open_box() {
@@ -222,6 +532,406 @@ mod tests {
             .set_icon_type(IconType::ICON_WARNING).set_window_type(WindowType::ABORT_RETRY_IGNORE)
             .show();
     }
+    #[test]
+    #[cfg(unix)]
+    fn read_only_attribute() {
+        use crate::metadata::attribute::{has_attribute, remove_attribute, set_attributes_exact};
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join("se_read_only_attribute.test");
+        File::create(&path).unwrap().write_all(b"Howdy from System-extensions").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(set_attribute(&path, Attributes::READ_ONLY));
+        assert_eq!(std::fs::read(&path).unwrap(), b"Howdy from System-extensions");
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o444);
+        assert!(has_attribute(&path, Attributes::READ_ONLY));
+
+        remove_attribute(&path, Attributes::READ_ONLY).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o644);
+        set_attributes_exact(&path, Attributes::READ_ONLY).unwrap();
+        assert_eq!(get_attributes(&path).unwrap(), vec![Attributes::READ_ONLY]);
+        set_attributes_exact(&path, Attributes::empty()).unwrap();
+        assert!(!has_attribute(&path, Attributes::READ_ONLY));
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn hide_and_unhide() {
+        use crate::metadata::attribute::{hide, unhide, hide_in_listing, unhide_in_listing, is_hidden_in_listing};
+
+        let dir = std::env::temp_dir().join("se_hide_and_unhide");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file.txt");
+        File::create(&path).unwrap();
+
+        let hidden = hide(&path).unwrap();
+        assert_eq!(hidden, dir.join(".file.txt"));
+        assert!(hidden.exists() && !path.exists());
+        assert_eq!(hide(&hidden).unwrap(), hidden);
+        assert_eq!(unhide(&hidden).unwrap(), path);
+
+        // Hiding must not replace an existing file.
+        File::create(dir.join(".file.txt")).unwrap();
+        assert_eq!(hide(&path).unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::create_dir_all(dir.join(".sub")).unwrap();
+        assert_eq!(hide(&dir.join("sub")).unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+        std::fs::remove_dir(dir.join(".sub")).unwrap();
+        assert_eq!(hide(&dir.join("sub")).unwrap(), dir.join(".sub"));
+
+        hide_in_listing(&path).unwrap();
+        hide_in_listing(&path).unwrap();
+        assert!(is_hidden_in_listing(&path).unwrap());
+        assert_eq!(std::fs::read_to_string(dir.join(".hidden")).unwrap(), "file.txt\n");
+        unhide_in_listing(&path).unwrap();
+        assert!(!is_hidden_in_listing(&path).unwrap());
+        assert!(!dir.join(".hidden").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn xattr() {
+        use crate::metadata::xattr::{self, Namespace, XattrError};
+
+        let path = std::env::temp_dir().join("se_xattr.test");
+        File::create(&path).unwrap();
+        let name = Namespace::User.attribute("se.origin");
+        match xattr::set(&path, &name, b"https://example.com") {
+            Err(XattrError::NotSupported) => return,
+            result => result.unwrap(),
+        }
+        assert_eq!(xattr::get(&path, &name).unwrap(), b"https://example.com");
+        assert!(xattr::list(&path).unwrap().contains(&name));
+
+        let file = File::open(&path).unwrap();
+        xattr::set_fd(&file, &name, b"").unwrap();
+        assert_eq!(xattr::get_fd(&file, &name).unwrap(), b"");
+        xattr::remove_fd(&file, &name).unwrap();
+        assert!(matches!(xattr::get(&path, &name), Err(XattrError::NoData)));
+        assert!(matches!(xattr::remove_nofollow(&path, &name), Err(XattrError::NoData)));
+        assert!(matches!(xattr::get(Path::new("./does_not_exist.test"), &name), Err(XattrError::Io(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn inode_flags() {
+        use crate::metadata::attribute::{get_inode_flags, set_inode_flags, remove_inode_flags, InodeFlags};
+
+        let path = std::env::temp_dir().join("se_inode_flags.test");
+        File::create(&path).unwrap();
+        let flags = match get_inode_flags(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return,
+            result => result.unwrap(),
+        };
+        assert!(!flags.contains(InodeFlags::NODUMP));
+        match set_inode_flags(&path, InodeFlags::NODUMP) {
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return,
+            result => result.unwrap(),
+        }
+        assert!(get_inode_flags(&path).unwrap().contains(InodeFlags::NODUMP));
+        remove_inode_flags(&path, InodeFlags::NODUMP).unwrap();
+        assert_eq!(get_inode_flags(&path).unwrap(), flags);
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn dos_attributes() {
+        use crate::metadata::attribute::{has_attribute, remove_attribute};
+        use crate::metadata::xattr::{self, XattrError};
+
+        let path = std::env::temp_dir().join("se_dos_attributes.test");
+        File::create(&path).unwrap();
+        // A version 3 value written by Samba: archive, with a creation time.
+        let mut samba = b"0x20\0\0\x03\0\x03\0\0\0".to_vec();
+        samba.extend_from_slice(&[0x11, 0, 0, 0, 0x20, 0, 0, 0]);
+        samba.extend_from_slice(&[0; 20]);
+        samba.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        samba.extend_from_slice(&[0; 8]);
+        match xattr::set(&path, "user.DOSATTRIB", &samba) {
+            Err(XattrError::NotSupported) => return,
+            result => result.unwrap(),
+        }
+        assert!(has_attribute(&path, Attributes::ARCHIVE));
+        assert!(!has_attribute(&path, Attributes::SYSTEM));
+
+        assert!(set_attribute(&path, Attributes::SYSTEM | Attributes::OFFLINE));
+        // The longer hex string moves the version, but the info stays aligned at 12.
+        let mut expected = b"0x1024\0\0\x03\0\x03\0".to_vec();
+        expected.extend_from_slice(&samba[12..]);
+        expected[16..20].copy_from_slice(&0x1024u32.to_le_bytes());
+        assert_eq!(xattr::get(&path, "user.DOSATTRIB").unwrap(), expected);
+        assert_eq!(get_attributes(&path).unwrap(), vec![Attributes::SYSTEM, Attributes::ARCHIVE, Attributes::OFFLINE]);
+
+        remove_attribute(&path, Attributes::ARCHIVE | Attributes::SYSTEM | Attributes::OFFLINE).unwrap();
+        assert!(get_attributes(&path).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    #[cfg(unix)]
+    fn permission_modes() {
+        use crate::metadata::permissions::{Mode, ModeChange, get_mode, chmod, chmod_recursive};
+
+        let parse = |expression: &str| expression.parse::<ModeChange>().unwrap();
+        let mode = |octal: &str| Mode::from_octal(octal).unwrap();
+        assert_eq!(parse("u+x,g-w,o=r").apply(mode("664"), false), mode("744"));
+        assert_eq!(parse("a+X").apply(mode("644"), false), mode("644"));
+        assert_eq!(parse("a+X").apply(mode("644"), true), mode("755"));
+        assert_eq!(parse("g=u,o-rwx").apply(mode("751"), false), mode("770"));
+        assert_eq!(parse("u+s,+t").apply(mode("755"), true), mode("5755"));
+        assert_eq!(parse("600").apply(mode("4755"), false), mode("600"));
+        assert_eq!(mode("4755").to_string(), "rwsr-xr-x");
+        assert_eq!(mode("1644").to_string(), "rw-r--r-T");
+        assert_eq!(mode("00755"), mode("755"));
+        assert_eq!(mode("00000"), Mode::empty());
+        assert!(Mode::from_octal("17555").is_err());
+        assert!("u+q".parse::<ModeChange>().is_err());
+        assert!("8".parse::<ModeChange>().is_err());
+
+        let dir = std::env::temp_dir().join("se_permission_modes");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        File::create(dir.join("sub").join("file.txt")).unwrap();
+        assert_eq!(chmod(&dir.join("sub").join("file.txt"), &parse("600")).unwrap(), mode("600"));
+        chmod(&dir.join("sub"), &parse("000")).unwrap();
+
+        chmod_recursive(&dir, &parse("u=rw,go=r"), &parse("755")).unwrap();
+        assert_eq!(get_mode(&dir.join("sub")).unwrap(), mode("755"));
+        assert_eq!(get_mode(&dir.join("sub").join("file.txt")).unwrap(), mode("644"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    #[cfg(unix)]
+    fn file_owner() {
+        use crate::metadata::owner::{owner_of, owner_of_nofollow, set_owner, set_owner_nofollow, resolve_user, Owner};
+
+        let (user, group) = owner_of(Path::new("/")).unwrap();
+        assert_eq!(user.id, 0);
+        assert_eq!(user.name.as_deref(), Some("root"));
+        assert_eq!(group.id, 0);
+        assert_eq!(resolve_user(&Owner::from("root")).unwrap(), 0);
+        assert_eq!(resolve_user(&Owner::from("1234")).unwrap(), 1234);
+        assert_eq!(resolve_user(&Owner::from("no-such-user-se")).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+        let path = std::env::temp_dir().join("se_file_owner.test");
+        let link = std::env::temp_dir().join("se_file_owner.link");
+        File::create(&path).unwrap();
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&path, &link).unwrap();
+        let (user, group) = owner_of(&path).unwrap();
+        // Setting the current owner is always allowed.
+        set_owner(&path, Some(user.id.into()), Some(group.id.into())).unwrap();
+        set_owner_nofollow(&link, None, Some(group.id.into())).unwrap();
+        assert_eq!(owner_of_nofollow(&link).unwrap().1.id, group.id);
+        assert_eq!(owner_of(&link).unwrap(), (user, group));
+        std::fs::remove_file(&link).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn posix_acl() {
+        use crate::metadata::acl::{Acl, AclTag, AclPerms, get_acl, set_acl, get_default_acl, set_default_acl, remove_default_acl};
+
+        let text = "# file: test\nuser::rw-\nuser:1000:r-x\ngroup::r--\nmask::r-x\t#effective:r--\nother::---\n";
+        let acl: Acl = text.parse().unwrap();
+        assert_eq!(acl.get(AclTag::User(1000)), Some(AclPerms::READ | AclPerms::EXECUTE));
+        assert_eq!(acl.to_string(), "user::rw-\nuser:1000:r-x\ngroup::r--\nmask::r-x\nother::---\n");
+        assert_eq!(Acl::from_bytes(&acl.to_bytes()).unwrap(), acl);
+        assert_eq!(&acl.to_bytes()[..12], &[2, 0, 0, 0, 1, 0, 6, 0, 255, 255, 255, 255]);
+        assert_eq!("u::7,g::5,o::0,u:root:4".parse::<Acl>().unwrap().validate().unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert!("user:1:rwz".parse::<Acl>().is_err());
+        assert_eq!(Acl::parse_default(&acl.to_default_string()).unwrap(), acl);
+        assert_eq!(Acl::parse_default("d:u::rw-,d:g::r--,d:o::---").unwrap(), Acl::from_mode(0o640));
+        assert!(Acl::parse_default("default:user::rw-\nother::---").is_err());
+        assert!(Acl::parse(&acl.to_default_string()).is_err());
+
+        let dir = std::env::temp_dir().join("se_posix_acl");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::os::unix::fs::PermissionsExt::from_mode(0o750)).unwrap();
+        assert_eq!(get_acl(&dir).unwrap(), Acl::from_mode(0o750));
+        match set_acl(&dir, &acl) {
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return,
+            result => result.unwrap(),
+        }
+        assert_eq!(get_acl(&dir).unwrap(), acl);
+        assert_eq!(get_default_acl(&dir).unwrap(), None);
+        set_default_acl(&dir, &acl).unwrap();
+        assert_eq!(get_default_acl(&dir).unwrap(), Some(acl));
+        remove_default_acl(&dir).unwrap();
+        assert_eq!(get_default_acl(&dir).unwrap(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    #[cfg(unix)]
+    fn inspect_file() {
+        use crate::metadata::{inspect, inspect_nofollow, FileKind};
+        use crate::metadata::attribute::has_attribute;
+        use crate::metadata::permissions::Mode;
+        use crate::metadata::time::get_modified_date;
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = std::env::temp_dir().join("se_inspect_file");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".file.txt");
+        File::create(&path).unwrap().write_all(b"Howdy").unwrap();
+        std::fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o000)).unwrap();
+        std::os::unix::fs::symlink(&path, dir.join("link")).unwrap();
+
+        let meta = inspect(&path).unwrap();
+        let std_meta = std::fs::metadata(&path).unwrap();
+        assert_eq!(meta.kind, FileKind::File);
+        assert_eq!(meta.size, 5);
+        assert_eq!(meta.mode, Mode::empty());
+        assert_eq!(meta.attributes, Attributes::HIDDEN | Attributes::READ_ONLY);
+        assert!(has_attribute(&path, Attributes::READ_ONLY));
+        assert_eq!((meta.inode, meta.uid, meta.links), (std_meta.ino(), std_meta.uid(), 1));
+        assert_eq!(meta.modified, get_modified_date(&path).unwrap());
+
+        assert_eq!(inspect(&dir.join("link")).unwrap().inode, meta.inode);
+        assert_eq!(inspect_nofollow(&dir.join("link")).unwrap().kind, FileKind::Symlink);
+        assert_eq!(inspect(&dir).unwrap().kind, FileKind::Directory);
+        assert_eq!(inspect(Path::new("/dev/null")).unwrap().kind, FileKind::CharDevice);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn copy_file_metadata() {
+        use crate::metadata::{copy_metadata, CopyOptions, MetadataItem};
+        use crate::metadata::acl::{get_acl, set_acl, Acl};
+        use crate::metadata::attribute::has_attribute;
+        use crate::metadata::permissions::{get_mode, Mode};
+        use crate::metadata::xattr;
+
+        let dir = std::env::temp_dir().join("se_copy_metadata");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (src, dst) = (dir.join("src.txt"), dir.join("dst.txt"));
+        File::create(&src).unwrap().write_all(b"Howdy").unwrap();
+        File::create(&dst).unwrap();
+        match xattr::set(&src, "user.se.test", b"value") {
+            Err(xattr::XattrError::NotSupported) => return,
+            result => result.unwrap(),
+        }
+        set_attribute(&src, Attributes::ARCHIVE);
+        match set_acl(&src, &"u::rw-,u:12345:r--,g::r--,m::r--,o::---".parse::<Acl>().unwrap()) {
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return,
+            result => result.unwrap(),
+        }
+        set_changed_date(&src, &FileTime::new(1, 2, 2003).unwrap()).unwrap();
+
+        copy_metadata(&src, &dst, &CopyOptions::new()).unwrap();
+        assert_eq!(xattr::get(&dst, "user.se.test").unwrap(), b"value");
+        assert!(has_attribute(&dst, Attributes::ARCHIVE));
+        assert_eq!(get_acl(&dst).unwrap(), get_acl(&src).unwrap());
+        assert_eq!(get_mode(&dst).unwrap(), Mode::from_octal("640").unwrap());
+        assert_eq!(std::fs::metadata(&dst).unwrap().modified().unwrap(), std::fs::metadata(&src).unwrap().modified().unwrap());
+
+        let mut options = CopyOptions::none();
+        options.set_permissions(true);
+        std::fs::set_permissions(&src, std::os::unix::fs::PermissionsExt::from_mode(0o4711)).unwrap();
+        copy_metadata(&src, &dst, &options).unwrap();
+        assert_eq!(get_mode(&dst).unwrap(), Mode::from_octal("4711").unwrap());
+
+        let (plain, plain_copy) = (dir.join("plain.txt"), dir.join("plain_copy.txt"));
+        File::create(&plain).unwrap();
+        File::create(&plain_copy).unwrap();
+        copy_metadata(&plain, &plain_copy, &CopyOptions::new()).unwrap();
+        assert!(matches!(xattr::get(&plain_copy, "user.DOSATTRIB"), Err(xattr::XattrError::NoData)));
+
+        let failures = copy_metadata(&dir.join("missing"), &dst, &options).unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, MetadataItem::Permissions);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn xdg_attributes() {
+        use crate::metadata::xdg::{self, XdgAttribute};
+
+        let path = std::env::temp_dir().join("se_xdg_attributes.test");
+        File::create(&path).unwrap();
+        assert_eq!(xdg::get_tags(&path).unwrap(), Vec::<String>::new());
+        assert_eq!(xdg::get_rating(&path).unwrap(), None);
+
+        match xdg::set_tags(&path, &["beach", " 2021 "]) {
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return,
+            result => result.unwrap(),
+        }
+        xdg::add_tag(&path, "family").unwrap();
+        xdg::remove_tag(&path, "beach").unwrap();
+        assert_eq!(crate::metadata::xattr::get(&path, "user.xdg.tags").unwrap(), b"2021,family");
+        assert!(xdg::add_tag(&path, "a,b").is_err());
+
+        xdg::set_origin_url(&path, "https://example.com/file.zip").unwrap();
+        xdg::set_rating(&path, 7).unwrap();
+        assert!(xdg::set_rating(&path, 11).is_err());
+        assert_eq!(xdg::get_origin_url(&path).unwrap().as_deref(), Some("https://example.com/file.zip"));
+        assert_eq!(xdg::list(&path).unwrap(), vec![
+            XdgAttribute::Tags(vec!["2021".to_string(), "family".to_string()]),
+            XdgAttribute::OriginUrl("https://example.com/file.zip".to_string()),
+            XdgAttribute::Rating(7),
+        ]);
+
+        xdg::set_tags::<&str>(&path, &[]).unwrap();
+        assert_eq!(xdg::list(&path).unwrap().len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn file_capabilities() {
+        use crate::metadata::file_capabilities::{get_capabilities, remove_capabilities, set_capabilities,
+                                                 Capabilities, FileCapabilities};
+
+        let caps: FileCapabilities = "cap_net_raw,cap_net_admin+p cap_chown+i".parse().unwrap();
+        assert_eq!(caps.permitted, Capabilities::NET_RAW | Capabilities::NET_ADMIN);
+        assert_eq!(caps.inheritable, Capabilities::CHOWN);
+        assert!(!caps.effective);
+        assert_eq!(caps.to_string(), "cap_chown=i cap_net_admin,cap_net_raw=p");
+        assert!("cap_setuid=eip cap_chown=ip".parse::<FileCapabilities>().is_err());
+        assert!("cap_unknown=p".parse::<FileCapabilities>().is_err());
+
+        let bind: FileCapabilities = "cap_net_bind_service=ep".parse().unwrap();
+        assert_eq!(bind.to_bytes(), [0x01, 0, 0, 0x02, 0, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(bind.to_string(), "cap_net_bind_service=ep");
+        let most: FileCapabilities = "=p cap_kill-p [rootid=1000]".parse().unwrap();
+        assert_eq!(most.to_string(), "=p cap_kill-p [rootid=1000]");
+        assert_eq!(FileCapabilities::from_bytes(&most.to_bytes()).unwrap(), most);
+        assert_eq!(FileCapabilities::new().to_string(), "=");
+
+        // Setting capabilities requires root.
+        if unsafe { libc::geteuid() } == 0 {
+            let path = std::env::temp_dir().join("se_file_capabilities.test");
+            File::create(&path).unwrap();
+            assert_eq!(get_capabilities(&path).unwrap(), None);
+            set_capabilities(&path, &bind).unwrap();
+            assert_eq!(get_capabilities(&path).unwrap(), Some(bind));
+            remove_capabilities(&path).unwrap();
+            assert_eq!(get_capabilities(&path).unwrap(), None);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+    #[test]
+    fn filesystem_info() {
+        use crate::metadata::filesystem_of;
+
+        let root = filesystem_of(Path::new("/")).unwrap();
+        assert_eq!(root.mount_point, Path::new("/"));
+        assert!(!root.filesystem_type.is_empty());
+        assert!(root.total_bytes >= root.free_bytes && root.free_bytes >= root.available_bytes);
+        assert!(root.block_size > 0);
+        assert!(root.mount_options.iter().any(|o| o == "ro" || o == "rw"));
+        assert_eq!(root.read_only, root.mount_options.iter().any(|o| o == "ro"));
+
+        let dir = std::env::temp_dir().join("se_filesystem_info");
+        std::fs::create_dir_all(&dir).unwrap();
+        let fs = filesystem_of(&dir).unwrap();
+        assert!(std::fs::canonicalize(&dir).unwrap().starts_with(&fs.mount_point));
+        assert_eq!(fs.used_bytes(), fs.total_bytes - fs.free_bytes);
+        assert!(filesystem_of(&dir.join("missing")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
     fn get_test_process() -> &'static str {
         if  cfg!(target_os = "macos") {
             "launchd"
@@ -0,0 +1,543 @@
+use std::fmt;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::metadata::owner::{resolve_group, resolve_user, Owner};
+use crate::metadata::xattr::{self, XattrError};
+
+bitflags! {
+    /**
+        The permissions of an ACL entry.
+    */
+    pub struct AclPerms: u16 {
+        /// Read the file, or list the directory.
+        const READ = 4;
+        /// Write to the file, or create files in the directory.
+        const WRITE = 2;
+        /// Execute the file, or search the directory.
+        const EXECUTE = 1;
+    }
+}
+
+impl fmt::Display for AclPerms {
+    /**
+        Formats the permissions the same as `getfacl`. (ex: `r-x`)
+    */
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}",
+               if self.contains(AclPerms::READ) { 'r' } else { '-' },
+               if self.contains(AclPerms::WRITE) { 'w' } else { '-' },
+               if self.contains(AclPerms::EXECUTE) { 'x' } else { '-' })
+    }
+}
+
+/**
+    Who an ACL entry applies to.
+
+    The variants are in the order the entries are stored in.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AclTag {
+    /// The owner of the file. (`user::`)
+    UserObj,
+    /// A user by id. (`user:1000:`)
+    User(u32),
+    /// The group of the file. (`group::`)
+    GroupObj,
+    /// A group by id. (`group:100:`)
+    Group(u32),
+    /// The most permissions any group class entry (named users, the group and named groups) can grant. (`mask::`)
+    Mask,
+    /// Everyone else. (`other::`)
+    Other,
+}
+
+/**
+    A single entry of an [`Acl`].
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AclEntry {
+    /// Who the entry applies to.
+    pub tag: AclTag,
+    /// The permissions granted.
+    pub perms: AclPerms,
+}
+
+/**
+    A POSIX.1e access control list.
+
+    The entries are always kept sorted in the order the kernel requires.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::acl::{Acl, AclTag, AclPerms};
+
+    let mut acl: Acl = "user::rw-,group::r--,other::---".parse().unwrap();
+    acl.set(AclTag::User(1000), AclPerms::READ | AclPerms::WRITE);
+    acl.calculate_mask();
+    assert_eq!(acl.to_string(), "user::rw-\nuser:1000:rw-\ngroup::r--\nmask::rw-\nother::---\n");
+    ```
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Acl {
+    entries: Vec<AclEntry>,
+}
+
+/// The version of the xattr format, from linux/posix_acl_xattr.h.
+const ACL_XATTR_VERSION: u32 = 2;
+const ACL_UNDEFINED_ID: u32 = u32::MAX;
+
+const ACL_USER_OBJ: u16 = 0x01;
+const ACL_USER: u16 = 0x02;
+const ACL_GROUP_OBJ: u16 = 0x04;
+const ACL_GROUP: u16 = 0x08;
+const ACL_MASK: u16 = 0x10;
+const ACL_OTHER: u16 = 0x20;
+
+const ACCESS_XATTR: &str = "system.posix_acl_access";
+const DEFAULT_XATTR: &str = "system.posix_acl_default";
+
+impl Acl {
+    /**
+        Construct an empty ACL.
+    */
+    pub fn new() -> Acl {
+        Acl { entries: Vec::new() }
+    }
+
+    /**
+        Construct the minimal ACL that is equal to the permission bits of a mode.
+
+        # Params
+        mode: u32 -> The mode of a file. (ex: `0o640`)<br>
+
+        # Returns
+        The ACL with the `user::`, `group::` and `other::` entries.
+    */
+    pub fn from_mode(mode: u32) -> Acl {
+        let perms = |shift: u32| AclPerms::from_bits_truncate(((mode >> shift) & 0o7) as u16);
+        Acl {
+            entries: vec![
+                AclEntry { tag: AclTag::UserObj, perms: perms(6) },
+                AclEntry { tag: AclTag::GroupObj, perms: perms(3) },
+                AclEntry { tag: AclTag::Other, perms: perms(0) },
+            ]
+        }
+    }
+
+    /**
+        Get the entries of the ACL, in order.
+    */
+    pub fn entries(&self) -> &[AclEntry] {
+        &self.entries
+    }
+
+    /**
+        Get the permissions of an entry.
+
+        # Params
+        tag: [`AclTag`] -> The entry to get.<br>
+
+        # Returns
+        Option<[`AclPerms`]> -> The permissions, or None if the ACL has no such entry.
+    */
+    pub fn get(&self, tag: AclTag) -> Option<AclPerms> {
+        self.entries.iter().find(|e| e.tag == tag).map(|e| e.perms)
+    }
+
+    /**
+        Add an entry, or replace the permissions of an existing entry.
+
+        **Note:** The mask is not changed, see [`Acl::calculate_mask`].
+
+        # Params
+        tag: [`AclTag`] -> The entry to set.<br>
+        perms: [`AclPerms`] -> The permissions to grant.<br>
+
+        # Returns
+        A mutable instance of the Acl.
+    */
+    pub fn set(&mut self, tag: AclTag, perms: AclPerms) -> &mut Self {
+        match self.entries.binary_search_by(|e| e.tag.cmp(&tag)) {
+            Ok(index) => self.entries[index].perms = perms,
+            Err(index) => self.entries.insert(index, AclEntry { tag, perms }),
+        }
+        self
+    }
+
+    /**
+        Remove an entry.
+
+        # Params
+        tag: [`AclTag`] -> The entry to remove.<br>
+
+        # Returns
+        A mutable instance of the Acl.
+    */
+    pub fn remove(&mut self, tag: AclTag) -> &mut Self {
+        self.entries.retain(|e| e.tag != tag);
+        self
+    }
+
+    /**
+        Set the mask to the union of the named user, group and named group entries,
+        the same as `setfacl` does by default.
+        The mask is removed if there are no named entries.
+
+        # Returns
+        A mutable instance of the Acl.
+    */
+    pub fn calculate_mask(&mut self) -> &mut Self {
+        let named = self.entries.iter().any(|e| matches!(e.tag, AclTag::User(_) | AclTag::Group(_)));
+        if !named {
+            return self.remove(AclTag::Mask);
+        }
+        let mask = self.entries.iter()
+            .filter(|e| matches!(e.tag, AclTag::User(_) | AclTag::GroupObj | AclTag::Group(_)))
+            .fold(AclPerms::empty(), |mask, e| mask | e.perms);
+        self.set(AclTag::Mask, mask)
+    }
+
+    /**
+        Check that the ACL can be stored: it needs the `user::`, `group::` and `other::` entries,
+        and a mask if it has named entries.
+
+        # Returns
+        io::Result<()> -> An error of kind `InvalidInput` describing what is wrong.
+    */
+    pub fn validate(&self) -> io::Result<()> {
+        for (tag, name) in [(AclTag::UserObj, "user::"), (AclTag::GroupObj, "group::"), (AclTag::Other, "other::")] {
+            if self.get(tag).is_none() {
+                return Err(invalid_input(format!("The ACL is missing the {} entry", name)));
+            }
+        }
+        let named = self.entries.iter().any(|e| matches!(e.tag, AclTag::User(_) | AclTag::Group(_)));
+        if named && self.get(AclTag::Mask).is_none() {
+            return Err(invalid_input("The ACL has named entries but no mask::".to_string()));
+        }
+        Ok(())
+    }
+
+    /**
+        Decode the `system.posix_acl_access` or `system.posix_acl_default` xattr format.
+
+        # Params
+        bytes: &[u8] -> The value of the xattr.<br>
+
+        # Returns
+        io::Result<Acl> -> The ACL, or an error of kind `InvalidData` if the value is not a valid ACL.
+    */
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Acl> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid POSIX ACL xattr");
+        if bytes.len() < 4 || bytes.len() % 8 != 4 {
+            return Err(invalid());
+        }
+        if u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) != ACL_XATTR_VERSION {
+            return Err(invalid());
+        }
+
+        let mut acl = Acl::new();
+        for entry in bytes[4..].chunks(8) {
+            let tag = u16::from_le_bytes([entry[0], entry[1]]);
+            let perms = AclPerms::from_bits_truncate(u16::from_le_bytes([entry[2], entry[3]]));
+            let id = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+            let tag = match tag {
+                ACL_USER_OBJ => AclTag::UserObj,
+                ACL_USER => AclTag::User(id),
+                ACL_GROUP_OBJ => AclTag::GroupObj,
+                ACL_GROUP => AclTag::Group(id),
+                ACL_MASK => AclTag::Mask,
+                ACL_OTHER => AclTag::Other,
+                _ => return Err(invalid()),
+            };
+            acl.set(tag, perms);
+        }
+        Ok(acl)
+    }
+
+    /**
+        Encode the ACL in the `system.posix_acl_access` and `system.posix_acl_default` xattr format.
+
+        # Returns
+        Vec<u8> -> The value of the xattr.
+    */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = ACL_XATTR_VERSION.to_le_bytes().to_vec();
+        for entry in &self.entries {
+            let (tag, id) = match entry.tag {
+                AclTag::UserObj => (ACL_USER_OBJ, ACL_UNDEFINED_ID),
+                AclTag::User(id) => (ACL_USER, id),
+                AclTag::GroupObj => (ACL_GROUP_OBJ, ACL_UNDEFINED_ID),
+                AclTag::Group(id) => (ACL_GROUP, id),
+                AclTag::Mask => (ACL_MASK, ACL_UNDEFINED_ID),
+                AclTag::Other => (ACL_OTHER, ACL_UNDEFINED_ID),
+            };
+            bytes.extend_from_slice(&tag.to_le_bytes());
+            bytes.extend_from_slice(&entry.perms.bits.to_le_bytes());
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        bytes
+    }
+
+    /**
+        Parse an ACL in the `getfacl` or `setfacl` text format.
+
+        Entries are separated by new lines or `,`. Each entry is `tag:qualifier:perms`, where the tag is
+        `user`, `group`, `mask` or `other` (or `u`, `g`, `m`, `o`), the qualifier is empty or a
+        name or id, and the perms are `rwx` letters (with `-` allowed) or an octal digit.
+        Comments starting with `#` are ignored.
+
+        # Params
+        text: &str -> The ACL text.<br>
+
+        # Returns
+        io::Result<Acl> -> The ACL, or an error if the text is invalid or a name does not exist.
+    */
+    pub fn parse(text: &str) -> io::Result<Acl> {
+        Acl::parse_entries(text, false)
+    }
+
+    /**
+        Parse default ACL entries in the `getfacl` or `setfacl` text format, such as the output of
+        [`Acl::to_default_string`].
+
+        Every entry must start with `default:` (or `d:`), otherwise the format is the same as [`Acl::parse`].
+
+        # Params
+        text: &str -> The default ACL text.<br>
+
+        # Returns
+        io::Result<Acl> -> The ACL, or an error if the text is invalid, has access entries or a name does not exist.
+    */
+    pub fn parse_default(text: &str) -> io::Result<Acl> {
+        Acl::parse_entries(text, true)
+    }
+
+    /**
+        Parse ACL entries, which must all be default entries if `default` is true and none otherwise.
+    */
+    fn parse_entries(text: &str, default: bool) -> io::Result<Acl> {
+        let mut acl = Acl::new();
+        for entry in text.split(['\n', ',']) {
+            let entry = entry.split('#').next().unwrap_or("").trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let access_entry = entry.strip_prefix("default:").or_else(|| entry.strip_prefix("d:"));
+            let entry = match (access_entry, default) {
+                (Some(access_entry), true) => access_entry,
+                (None, false) => entry,
+                (Some(_), false) => return Err(invalid_input(format!("Default entries are not part of an access ACL: {}", entry))),
+                (None, true) => return Err(invalid_input(format!("Access entries are not part of a default ACL: {}", entry))),
+            };
+            let parts: Vec<&str> = entry.split(':').collect();
+            let (tag, qualifier, perms) = match parts.as_slice() {
+                [tag, qualifier, perms] => (*tag, *qualifier, *perms),
+                // `mask` and `other` may leave out the qualifier.
+                [tag, perms] => (*tag, "", *perms),
+                _ => return Err(invalid_input(format!("Invalid ACL entry: {}", entry))),
+            };
+            let tag = match (tag, qualifier) {
+                ("user" | "u", "") => AclTag::UserObj,
+                ("user" | "u", name) => AclTag::User(resolve_user(&Owner::from(name))?),
+                ("group" | "g", "") => AclTag::GroupObj,
+                ("group" | "g", name) => AclTag::Group(resolve_group(&Owner::from(name))?),
+                ("mask" | "m", "") => AclTag::Mask,
+                ("other" | "o", "") => AclTag::Other,
+                _ => return Err(invalid_input(format!("Invalid ACL entry: {}", entry))),
+            };
+            acl.set(tag, parse_perms(perms).ok_or_else(|| invalid_input(format!("Invalid ACL entry: {}", entry)))?);
+        }
+        Ok(acl)
+    }
+
+    /**
+        Format the ACL as default entries, the same as `getfacl` shows them for a directory.
+        (ex: `default:user::rwx`)
+    */
+    pub fn to_default_string(&self) -> String {
+        self.to_string().lines().map(|line| format!("default:{}\n", line)).collect()
+    }
+}
+
+impl fmt::Display for Acl {
+    /**
+        Formats the ACL the same as `getfacl -n`, one entry per line with numeric ids.
+    */
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            match entry.tag {
+                AclTag::UserObj => writeln!(f, "user::{}", entry.perms)?,
+                AclTag::User(id) => writeln!(f, "user:{}:{}", id, entry.perms)?,
+                AclTag::GroupObj => writeln!(f, "group::{}", entry.perms)?,
+                AclTag::Group(id) => writeln!(f, "group:{}:{}", id, entry.perms)?,
+                AclTag::Mask => writeln!(f, "mask::{}", entry.perms)?,
+                AclTag::Other => writeln!(f, "other::{}", entry.perms)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Acl {
+    type Err = io::Error;
+
+    /**
+        Parses the `getfacl` text format, see [`Acl::parse`].
+    */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Acl::parse(s)
+    }
+}
+
+fn parse_perms(perms: &str) -> Option<AclPerms> {
+    if let Some(digit) = perms.chars().next().filter(|_| perms.len() == 1).and_then(|c| c.to_digit(8)) {
+        return Some(AclPerms::from_bits_truncate(digit as u16));
+    }
+    let mut output = AclPerms::empty();
+    for c in perms.chars() {
+        match c {
+            'r' => output |= AclPerms::READ,
+            'w' => output |= AclPerms::WRITE,
+            'x' => output |= AclPerms::EXECUTE,
+            '-' => {}
+            _ => return None,
+        }
+    }
+    Some(output)
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn xattr_error(error: XattrError) -> io::Error {
+    match error {
+        XattrError::NotSupported => io::Error::new(io::ErrorKind::Unsupported, "The filesystem does not support POSIX ACLs"),
+        error => error.into(),
+    }
+}
+
+/**
+   Get the access ACL of a file. (Like `getfacl`.)
+
+   Files without an ACL return the minimal ACL of their permission bits.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<[`Acl`]> -> The ACL of the file.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::acl::get_acl;
+   use std::path::Path;
+
+   print!("{}", get_acl(Path::new("/")).unwrap());
+   ```
+*/
+pub fn get_acl(path: &Path) -> io::Result<Acl> {
+    match xattr::get(path, ACCESS_XATTR) {
+        Ok(bytes) => Acl::from_bytes(&bytes),
+        Err(XattrError::NoData) | Err(XattrError::NotSupported) =>
+            Ok(Acl::from_mode(std::fs::metadata(path)?.permissions().mode())),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/**
+   Set the access ACL of a file. (Like `setfacl --set`.)
+   This also changes the permission bits of the file.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   acl: &[`Acl`] -> The new ACL.
+
+   ## Returns
+   io::Result<()> -> An error if the ACL is invalid, or of kind `Unsupported` if the filesystem does not support ACLs.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::acl::{get_acl, set_acl, AclTag, AclPerms};
+   use std::path::Path;
+
+   let path = Path::new("/srv/shared");
+   if let Ok(mut acl) = get_acl(path) {
+       acl.set(AclTag::User(1000), AclPerms::READ | AclPerms::EXECUTE).calculate_mask();
+       set_acl(path, &acl);
+   }
+   ```
+*/
+pub fn set_acl(path: &Path, acl: &Acl) -> io::Result<()> {
+    acl.validate()?;
+    xattr::set(path, ACCESS_XATTR, &acl.to_bytes()).map_err(xattr_error)
+}
+
+/**
+   Remove the extended entries of the access ACL of a file, leaving only the permission bits.
+   The group permission bits keep the value of the old mask.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<()> -> An error if the ACL could not be removed. Nothing happens if the file has no extended entries.
+*/
+pub fn remove_acl(path: &Path) -> io::Result<()> {
+    match xattr::remove(path, ACCESS_XATTR) {
+        Ok(()) | Err(XattrError::NoData) | Err(XattrError::NotSupported) => Ok(()),
+        Err(e) => Err(xattr_error(e)),
+    }
+}
+
+/**
+   Get the default ACL of a directory, which new files in it inherit.
+
+   ## Params
+   dir: &Path -> The path to the directory.
+
+   ## Returns
+   io::Result<Option<[`Acl`]>> -> The default ACL, or None if the directory does not have one.
+*/
+pub fn get_default_acl(dir: &Path) -> io::Result<Option<Acl>> {
+    match xattr::get(dir, DEFAULT_XATTR) {
+        Ok(bytes) => Acl::from_bytes(&bytes).map(Some),
+        Err(XattrError::NoData) | Err(XattrError::NotSupported) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/**
+   Set the default ACL of a directory, which new files in it inherit. (Like `setfacl -d --set`.)
+
+   ## Params
+   dir: &Path -> The path to the directory. <br>
+   acl: &[`Acl`] -> The new default ACL.
+
+   ## Returns
+   io::Result<()> -> An error if the ACL is invalid, or of kind `Unsupported` if the filesystem does not support ACLs.
+*/
+pub fn set_default_acl(dir: &Path, acl: &Acl) -> io::Result<()> {
+    acl.validate()?;
+    xattr::set(dir, DEFAULT_XATTR, &acl.to_bytes()).map_err(xattr_error)
+}
+
+/**
+   Remove the default ACL of a directory. (Like `setfacl -k`.)
+
+   ## Params
+   dir: &Path -> The path to the directory.
+
+   ## Returns
+   io::Result<()> -> An error if the default ACL could not be removed. Nothing happens if there is none
+   (including on filesystems without ACLs).
+*/
+pub fn remove_default_acl(dir: &Path) -> io::Result<()> {
+    match xattr::remove(dir, DEFAULT_XATTR) {
+        Ok(()) | Err(XattrError::NoData) | Err(XattrError::NotSupported) => Ok(()),
+        Err(e) => Err(xattr_error(e)),
+    }
+}
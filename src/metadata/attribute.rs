@@ -2,7 +2,8 @@
 extern crate winapi;
 
 use std::ffi::CString;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 
 
 
@@ -23,7 +24,7 @@ bitflags! {
 
         /**
             Represents a hidden file.
-            (Windows Only. Put a '.' in front of a file on Linux.)
+            (On Linux the file is renamed to start with a '.', use [`hide`] to get the new path.)
         */
         const HIDDEN = 2;
 
@@ -31,6 +32,30 @@ bitflags! {
             Represents a file set to Read Only.
         */
         const READ_ONLY = 1;
+
+        /**
+            Represents a file used by the operating system.
+            (Stored in the `user.DOSATTRIB` extended attribute on Linux, the same as Samba.)
+        */
+        const SYSTEM = 4;
+
+        /**
+            Represents a file that should be archived (backed up).
+            (Stored in the `user.DOSATTRIB` extended attribute on Linux, the same as Samba.)
+        */
+        const ARCHIVE = 32;
+
+        /**
+            Represents a file that is being used for temporary storage.
+            (Stored in the `user.DOSATTRIB` extended attribute on Linux, the same as Samba.)
+        */
+        const TEMPORARY = 256;
+
+        /**
+            Represents a file whose data is not immediately available.
+            (Stored in the `user.DOSATTRIB` extended attribute on Linux, the same as Samba.)
+        */
+        const OFFLINE = 4096;
     }
 }
 
@@ -38,7 +63,8 @@ bitflags! {
     A vector of all valid attributes.
 */
 // This exists so you can loop through attributes.
-const ATTRIBUTES: [Attributes; 3] = [Attributes::NORMAL, Attributes::HIDDEN, Attributes::READ_ONLY];
+const ATTRIBUTES: [Attributes; 7] = [Attributes::NORMAL, Attributes::HIDDEN, Attributes::READ_ONLY,
+    Attributes::SYSTEM, Attributes::ARCHIVE, Attributes::TEMPORARY, Attributes::OFFLINE];
 
 /**
     Set the attributes for a file.
@@ -155,6 +181,130 @@ pub fn get_attributes(file: &Path) -> Result<Vec<Attributes>, String> {
     }
 }
 
+/**
+    Remove attributes from a file, leaving the other attributes unchanged.
+
+   # Params
+   file: Path -> The path to the file. <br>
+   attrib: [`Attributes`] -> The attributes to remove. (Bit-wise OR can be used to remove multiple attributes.)
+   # Returns
+   io::Result<()> -> An error if the attributes could not be removed.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::{remove_attribute, Attributes};
+
+   remove_attribute(Path::new("/test.txt"), Attributes::READ_ONLY);
+   ```
+*/
+#[cfg(windows)]
+pub fn remove_attribute(file: &Path, attrib: Attributes) -> io::Result<()> {
+    use self::winapi::um::fileapi::GetFileAttributesA;
+    use self::winapi::um::fileapi::INVALID_FILE_ATTRIBUTES;
+
+    let file_string = CString::new(file.to_str().unwrap()).unwrap();
+    let bits = unsafe { GetFileAttributesA(file_string.as_ptr()) };
+    if bits == INVALID_FILE_ATTRIBUTES {
+        return Err(io::Error::last_os_error());
+    }
+    set_attribute_bits(&file_string, bits & !attrib.bits)
+}
+
+/**
+    Set the attributes of a file to exactly the attributes given.
+    Attributes that are not given are removed.
+
+   # Params
+   file: Path -> The path to the file. <br>
+   attrib: [`Attributes`] -> The attributes the file should have.
+   # Returns
+   io::Result<()> -> An error if the attributes could not be set.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::{set_attributes_exact, Attributes};
+
+   // Removes all other attributes.
+   set_attributes_exact(Path::new("/test.txt"), Attributes::READ_ONLY);
+   ```
+*/
+#[cfg(windows)]
+pub fn set_attributes_exact(file: &Path, attrib: Attributes) -> io::Result<()> {
+    let file_string = CString::new(file.to_str().unwrap()).unwrap();
+    set_attribute_bits(&file_string, attrib.bits)
+}
+
+/**
+    Hide a file by setting the [`Attributes::HIDDEN`] attribute.
+
+    The path is returned so this matches the Linux version, which renames the file.
+
+   # Params
+   file: Path -> The path to the file.
+   # Returns
+   io::Result<PathBuf> -> The path of the file, or an error if the attribute could not be set.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::hide;
+
+   let hidden = hide(Path::new("./dir/file.txt"));
+   ```
+*/
+#[cfg(windows)]
+pub fn hide(file: &Path) -> io::Result<PathBuf> {
+    use self::winapi::um::fileapi::GetFileAttributesA;
+    use self::winapi::um::fileapi::INVALID_FILE_ATTRIBUTES;
+
+    let file_string = CString::new(file.to_str().unwrap()).unwrap();
+    let bits = unsafe { GetFileAttributesA(file_string.as_ptr()) };
+    if bits == INVALID_FILE_ATTRIBUTES {
+        return Err(io::Error::last_os_error());
+    }
+    set_attribute_bits(&file_string, bits | Attributes::HIDDEN.bits)?;
+    Ok(file.to_path_buf())
+}
+
+/**
+    Unhide a file by removing the [`Attributes::HIDDEN`] attribute.
+
+   # Params
+   file: Path -> The path to the file.
+   # Returns
+   io::Result<PathBuf> -> The path of the file, or an error if the attribute could not be removed.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::unhide;
+
+   let shown = unhide(Path::new("./dir/file.txt"));
+   ```
+*/
+#[cfg(windows)]
+pub fn unhide(file: &Path) -> io::Result<PathBuf> {
+    remove_attribute(file, Attributes::HIDDEN)?;
+    Ok(file.to_path_buf())
+}
+
+#[cfg(windows)]
+fn set_attribute_bits(file: &CString, bits: u32) -> io::Result<()> {
+    use self::winapi::um::fileapi::SetFileAttributesA;
+
+    // Normal is only valid on its own, and a file without any attributes has to be set to normal.
+    let bits = match bits & !Attributes::NORMAL.bits {
+        0 => Attributes::NORMAL.bits,
+        other => other,
+    };
+    if unsafe { SetFileAttributesA(file.as_ptr(), bits) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
 /*
 
     Linux Section
@@ -188,30 +338,327 @@ pub fn get_attributes(file: &Path) -> Result<Vec<Attributes>, String> {
 */
 #[cfg(unix)]
 pub fn set_attribute(path: &Path, attrib: Attributes) -> bool {
+    if attrib.contains(Attributes::READ_ONLY) && set_read_only(path, true).is_err() {
+        return false;
+    }
+    if attrib.intersects(DOS_ATTRIBUTES) && update_dos_attributes(path, |bits| bits | attrib.bits).is_err() {
+        return false;
+    }
+    if attrib.contains(Attributes::HIDDEN) {
+        return hide(path).is_ok();
+    }
+    true
+}
+
+/**
+    Remove attributes from a file, leaving the other attributes unchanged.
+
+   # Params
+   file: Path -> The path to the file. <br>
+   attrib: [`Attributes`] -> The attributes to remove. (Bit-wise OR can be used to remove multiple attributes.)
+   # Returns
+   io::Result<()> -> An error if the attributes could not be removed.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::{remove_attribute, Attributes};
+
+   remove_attribute(Path::new("/test.txt"), Attributes::READ_ONLY);
+   ```
+*/
+#[cfg(unix)]
+pub fn remove_attribute(path: &Path, attrib: Attributes) -> io::Result<()> {
+    if attrib.contains(Attributes::READ_ONLY) {
+        set_read_only(path, false)?;
+    }
+    if attrib.intersects(DOS_ATTRIBUTES) {
+        update_dos_attributes(path, |bits| bits & !(attrib & DOS_ATTRIBUTES).bits)?;
+    }
+    if attrib.contains(Attributes::HIDDEN) {
+        unhide(path)?;
+    }
+    Ok(())
+}
+
+/**
+    Set the attributes of a file to exactly the attributes given.
+    Attributes that are not given are removed.
+
+    **Note:** Hiding a file on Linux renames it, so the path will no longer be valid
+    if [`Attributes::HIDDEN`] changes.
+
+   # Params
+   file: Path -> The path to the file. <br>
+   attrib: [`Attributes`] -> The attributes the file should have.
+   # Returns
+   io::Result<()> -> An error if the attributes could not be set.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::{set_attributes_exact, Attributes};
+
+   // Removes all other attributes.
+   set_attributes_exact(Path::new("/test.txt"), Attributes::READ_ONLY);
+   ```
+*/
+#[cfg(unix)]
+pub fn set_attributes_exact(path: &Path, attrib: Attributes) -> io::Result<()> {
+    set_read_only(path, attrib.contains(Attributes::READ_ONLY))?;
+    update_dos_attributes(path, |bits| (bits & !DOS_ATTRIBUTES.bits) | (attrib & DOS_ATTRIBUTES).bits)?;
+    if attrib.contains(Attributes::HIDDEN) {
+        hide(path)?;
+    } else {
+        unhide(path)?;
+    }
+    Ok(())
+}
+
+/// Add or clear the write bits of a file without touching its contents.
+/// Clearing removes every write bit, adding only gives write access back to the owner.
+#[cfg(unix)]
+pub(crate) fn set_read_only(path: &Path, read_only: bool) -> io::Result<()> {
     use std::fs;
-    use std::fs::set_permissions;
-    use std::fs::File;
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut perms = fs::metadata(path)?.permissions();
+    let mode = perms.mode();
+    let new_mode = if read_only { mode & !0o222 } else { mode | 0o200 };
+    if new_mode == mode {
+        return Ok(());
+    }
+    perms.set_mode(new_mode);
+    fs::set_permissions(path, perms)
+}
 
-    let bits = attrib.bits;
+/**
+    Hide a file by adding a '.' to the start of its name.
+    Nothing is changed if the file is already hidden.
 
-    if &bits & Attributes::HIDDEN.bits == Attributes::HIDDEN.bits {
-        fs::rename(path, format!(".{}", path.to_str().unwrap())).is_ok();
-    } else if &bits & Attributes::READ_ONLY.bits == Attributes::READ_ONLY.bits{
-        let result = File::create(path);
-        if result.is_err() {
-            return false;
+    Only the file name is changed, the file stays in the same directory.
+
+   # Params
+   file: Path -> The path to the file.
+   # Returns
+   io::Result<PathBuf> -> The new path of the file, or an error if a file with the new name already exists
+   or the file could not be renamed.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::hide;
+
+   let hidden = hide(Path::new("./dir/file.txt")); // Ok("./dir/.file.txt")
+   ```
+*/
+#[cfg(unix)]
+pub fn hide(path: &Path) -> io::Result<PathBuf> {
+    let name = file_name(path)?;
+    if name.starts_with('.') {
+        return Ok(path.to_path_buf());
+    }
+    rename_to(path, format!(".{}", name))
+}
+
+/**
+    Unhide a file by removing the '.' at the start of its name.
+    Nothing is changed if the file is not hidden.
+
+    Only the file name is changed, the file stays in the same directory.
+
+   # Params
+   file: Path -> The path to the file.
+   # Returns
+   io::Result<PathBuf> -> The new path of the file, or an error if a file with the new name already exists
+   or the file could not be renamed.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::unhide;
+
+   let shown = unhide(Path::new("./dir/.file.txt")); // Ok("./dir/file.txt")
+   ```
+*/
+#[cfg(unix)]
+pub fn unhide(path: &Path) -> io::Result<PathBuf> {
+    let name = file_name(path)?;
+    match name.strip_prefix('.') {
+        Some(stripped) if !stripped.is_empty() => rename_to(path, stripped.to_string()),
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/**
+    Hide a file without renaming it by adding its name to the `.hidden` file of its directory.
+
+    The `.hidden` file is honoured by most Linux file managers (Nautilus, Dolphin, Thunar),
+    but not by `ls` or other programs.
+
+   # Params
+   file: Path -> The path to the file.
+   # Returns
+   io::Result<()> -> An error if the `.hidden` file could not be read or written.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::hide_in_listing;
+
+   hide_in_listing(Path::new("./dir/file.txt"));
+   ```
+*/
+#[cfg(unix)]
+pub fn hide_in_listing(path: &Path) -> io::Result<()> {
+    let name = file_name(path)?;
+    let mut names = read_hidden_listing(path)?;
+    if names.iter().any(|n| n == name) {
+        return Ok(());
+    }
+    names.push(name.to_string());
+    write_hidden_listing(path, &names)
+}
+
+/**
+    Remove a file from the `.hidden` file of its directory.
+    The `.hidden` file is deleted if it becomes empty.
+
+   # Params
+   file: Path -> The path to the file.
+   # Returns
+   io::Result<()> -> An error if the `.hidden` file could not be read or written.
+
+   # Example
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::unhide_in_listing;
+
+   unhide_in_listing(Path::new("./dir/file.txt"));
+   ```
+*/
+#[cfg(unix)]
+pub fn unhide_in_listing(path: &Path) -> io::Result<()> {
+    let name = file_name(path)?;
+    let mut names = read_hidden_listing(path)?;
+    let length = names.len();
+    names.retain(|n| n != name);
+    if names.len() == length {
+        return Ok(());
+    }
+    write_hidden_listing(path, &names)
+}
+
+/**
+    Check if a file is listed in the `.hidden` file of its directory.
+
+   # Params
+   file: Path -> The path to the file.
+   # Returns
+   io::Result<bool> -> If the file is listed, or an error if the `.hidden` file could not be read.
+*/
+#[cfg(unix)]
+pub fn is_hidden_in_listing(path: &Path) -> io::Result<bool> {
+    let name = file_name(path)?;
+    Ok(read_hidden_listing(path)?.iter().any(|n| n == name))
+}
+
+#[cfg(unix)]
+fn file_name(path: &Path) -> io::Result<&str> {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "The path does not have a valid file name"))
+}
+
+/**
+    Rename a file within its directory, without replacing an existing file.
+
+    On Linux the rename fails atomically if the new name exists. Otherwise a file is hard linked to
+    its new name and then unlinked, which also cannot replace a file. A directory (or a file on a
+    filesystem without hard links) is only checked for an existing target before it is renamed,
+    so a target created in between is replaced.
+*/
+#[cfg(unix)]
+fn rename_to(path: &Path, name: String) -> io::Result<PathBuf> {
+    let new_path = path.with_file_name(name);
+    #[cfg(target_os = "linux")]
+    {
+        match rename_noreplace(path, &new_path) {
+            // The kernel or the filesystem does not support RENAME_NOREPLACE.
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) || e.raw_os_error() == Some(libc::ENOSYS) => {}
+            result => return result.map(|_| new_path),
         }
-        let file = result.unwrap();
-        let result_meta = file.metadata();
-        if result_meta.is_err() {
-            return false;
+    }
+    if !std::fs::symlink_metadata(path)?.is_dir() {
+        match std::fs::hard_link(path, &new_path) {
+            Ok(()) => {
+                std::fs::remove_file(path)?;
+                return Ok(new_path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Err(already_exists(&new_path)),
+            Err(_) => {}
         }
-        let meta = result_meta.unwrap();
-        let mut perms = meta.permissions();
-        perms.set_readonly(true);
-        return set_permissions(&path, perms).is_ok();
     }
-    true
+    if std::fs::symlink_metadata(&new_path).is_ok() {
+        return Err(already_exists(&new_path));
+    }
+    std::fs::rename(path, &new_path)?;
+    Ok(new_path)
+}
+
+/// Rename a path with `renameat2(RENAME_NOREPLACE)`, failing if the new path exists.
+#[cfg(target_os = "linux")]
+fn rename_noreplace(path: &Path, new_path: &Path) -> io::Result<()> {
+    use crate::metadata::time::path_to_cstring;
+
+    let (from, to) = (path_to_cstring(path)?, path_to_cstring(new_path)?);
+    // The syscall is used directly since the glibc wrapper is only available since glibc 2.28.
+    let result = unsafe {
+        libc::syscall(libc::SYS_renameat2, libc::AT_FDCWD, from.as_ptr(), libc::AT_FDCWD, to.as_ptr(),
+                      libc::RENAME_NOREPLACE)
+    };
+    if result == 0 {
+        return Ok(());
+    }
+    let error = io::Error::last_os_error();
+    if error.raw_os_error() == Some(libc::EEXIST) {
+        return Err(already_exists(new_path));
+    }
+    Err(error)
+}
+
+#[cfg(unix)]
+fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", path.display()))
+}
+
+/// The path of the `.hidden` file in the same directory as a file.
+#[cfg(unix)]
+fn hidden_listing_path(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.join(".hidden"),
+        _ => PathBuf::from(".hidden"),
+    }
+}
+
+#[cfg(unix)]
+fn read_hidden_listing(path: &Path) -> io::Result<Vec<String>> {
+    match std::fs::read_to_string(hidden_listing_path(path)) {
+        Ok(contents) => Ok(contents.lines().filter(|l| !l.is_empty()).map(String::from).collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(unix)]
+fn write_hidden_listing(path: &Path, names: &[String]) -> io::Result<()> {
+    let listing = hidden_listing_path(path);
+    if names.is_empty() {
+        return std::fs::remove_file(listing);
+    }
+    let mut contents = names.join("\n");
+    contents.push('\n');
+    std::fs::write(listing, contents)
 }
 
 /**
@@ -234,7 +681,7 @@ pub fn set_attribute(path: &Path, attrib: Attributes) -> bool {
 */
 #[cfg(unix)]
 pub fn has_attribute(file: &Path, attrib: Attributes) -> bool {
-    use std::fs::File;
+    use std::fs;
     if attrib ==Attributes::HIDDEN {
         let option = file.file_name();
         if option.is_none(){
@@ -247,18 +694,16 @@ pub fn has_attribute(file: &Path, attrib: Attributes) -> bool {
         }
         str.unwrap().starts_with(".")
     } else if attrib == Attributes::READ_ONLY{
-        let result = File::open(file);
-        if result.is_err() {
-            return false;
-        }
-        let file = result.unwrap();
-        let result_meta = file.metadata();
+        // The metadata can be read without opening the file, so this works for unreadable files.
+        let result_meta = fs::metadata(file);
         if result_meta.is_err() {
             return false;
         }
         let meta = result_meta.unwrap();
         meta.permissions().readonly()
-    }else {
+    } else if !attrib.is_empty() && DOS_ATTRIBUTES.contains(attrib) {
+        matches!(read_dos_attributes(file), Ok(bits) if bits & attrib.bits == attrib.bits)
+    } else {
         false
     }
 }
@@ -289,6 +734,349 @@ pub fn get_attributes(file: &Path) -> Result<Vec<Attributes>, String> {
     if has_attribute(file, Attributes::HIDDEN){
         values.push(Attributes::HIDDEN);
     }
+    let dos = read_dos_attributes(file).unwrap_or(0);
+    for att in ATTRIBUTES.iter().filter(|a| DOS_ATTRIBUTES.contains(**a)) {
+        if dos & att.bits == att.bits {
+            values.push(*att);
+        }
+    }
 
     Ok(values)
-}
\ No newline at end of file
+}
+
+/*
+
+    Samba DOS attributes section
+
+ */
+/// The attributes that are stored in `user.DOSATTRIB` on Unix.
+#[cfg(unix)]
+pub(crate) const DOS_ATTRIBUTES: Attributes = Attributes {
+    bits: Attributes::SYSTEM.bits | Attributes::ARCHIVE.bits | Attributes::TEMPORARY.bits | Attributes::OFFLINE.bits
+};
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+const DOSATTRIB: &str = "user.DOSATTRIB";
+
+/// The `valid_flags` bit that marks the attributes as set in version 2 and later.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+const XATTR_DOSINFO_ATTRIB: u32 = 0x1;
+
+/// Read the raw DOS attributes of a file, 0 if it has none.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn read_dos_attributes(path: &Path) -> io::Result<u32> {
+    use crate::metadata::xattr::{self, XattrError};
+
+    match xattr::get(path, DOSATTRIB) {
+        Ok(blob) => Ok(DosAttrib::parse(&blob).map_or(0, |d| d.attrib)),
+        Err(XattrError::NoData) | Err(XattrError::NotSupported) => Ok(0),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Change the raw DOS attributes of a file, keeping the rest of the Samba data.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn update_dos_attributes<F: Fn(u32) -> u32>(path: &Path, update: F) -> io::Result<()> {
+    use crate::metadata::xattr::{self, XattrError};
+
+    // A filesystem without xattrs has no DOS attributes, which is only an error if some have to be stored.
+    let existing = match xattr::get(path, DOSATTRIB) {
+        Ok(blob) => DosAttrib::parse(&blob),
+        Err(XattrError::NoData) | Err(XattrError::NotSupported) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let old = existing.as_ref().map_or(0, |d| d.attrib);
+    let new = update(old);
+    if new == old && (existing.is_some() || new == 0) {
+        return Ok(());
+    }
+    let mut dos = existing.unwrap_or_else(DosAttrib::new);
+    dos.set_attrib(new);
+    xattr::set(path, DOSATTRIB, &dos.to_bytes()).map_err(io::Error::from)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+pub(crate) fn read_dos_attributes(_path: &Path) -> io::Result<u32> {
+    Ok(0)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+pub(crate) fn update_dos_attributes<F: Fn(u32) -> u32>(_path: &Path, update: F) -> io::Result<()> {
+    if update(0) == 0 {
+        return Ok(());
+    }
+    Err(io::Error::new(io::ErrorKind::Unsupported, "DOS attributes are not supported on this platform"))
+}
+
+/**
+    The `user.DOSATTRIB` value written by Samba.
+
+    The value is NDR encoded: the attributes as a hex string ("0x20") ending with a 0,
+    then the version twice as a u16 and the version specific info, aligned to 4 bytes.
+    Versions 2 and later start the info with `valid_flags`, then the attributes.
+    Very old versions only have the hex string.
+*/
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+struct DosAttrib {
+    attrib: u32,
+    version: u16,
+    info: Vec<u8>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl DosAttrib {
+    /// A version 3 value with only the attributes set, which every Samba 4 version reads.
+    fn new() -> DosAttrib {
+        let mut info = vec![0u8; 44];
+        info[..4].copy_from_slice(&XATTR_DOSINFO_ATTRIB.to_le_bytes());
+        DosAttrib { attrib: 0, version: 3, info }
+    }
+
+    fn parse(blob: &[u8]) -> Option<DosAttrib> {
+        let end = blob.iter().position(|b| *b == 0).unwrap_or(blob.len());
+        let hex = std::str::from_utf8(&blob[..end]).ok()?.trim();
+        let hex = hex.strip_prefix("0x").or_else(|| hex.strip_prefix("0X")).unwrap_or(hex);
+        let mut dos = DosAttrib { attrib: u32::from_str_radix(hex, 16).ok()?, version: 0xFFFF, info: Vec::new() };
+
+        let offset = align(end + 1, 2);
+        if blob.len() < offset + 4 {
+            return Some(dos);
+        }
+        let version = u16::from_le_bytes([blob[offset], blob[offset + 1]]);
+        let info = blob[align(offset + 4, 4).min(blob.len())..].to_vec();
+        if let Some(attrib) = attrib_offset(version).and_then(|o| info.get(o..o + 4)) {
+            dos.attrib = u32::from_le_bytes([attrib[0], attrib[1], attrib[2], attrib[3]]);
+            dos.version = version;
+            dos.info = info;
+        }
+        Some(dos)
+    }
+
+    fn set_attrib(&mut self, attrib: u32) {
+        self.attrib = attrib;
+        if let Some(offset) = attrib_offset(self.version) {
+            self.info[offset..offset + 4].copy_from_slice(&attrib.to_le_bytes());
+            if offset == 4 {
+                let flags = u32::from_le_bytes([self.info[0], self.info[1], self.info[2], self.info[3]]);
+                self.info[..4].copy_from_slice(&(flags | XATTR_DOSINFO_ATTRIB).to_le_bytes());
+            }
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = format!("0x{:x}", self.attrib).into_bytes();
+        bytes.push(0);
+        if self.version == 0xFFFF {
+            return bytes;
+        }
+        bytes.resize(align(bytes.len(), 2), 0);
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.resize(align(bytes.len(), 4), 0);
+        bytes.extend_from_slice(&self.info);
+        bytes
+    }
+}
+
+/// Where the attributes are in the info of a version.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn attrib_offset(version: u16) -> Option<usize> {
+    match version {
+        1 => Some(0),
+        2..=5 => Some(4),
+        _ => None,
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn align(offset: usize, alignment: usize) -> usize {
+    offset + (alignment - offset % alignment) % alignment
+}
+/*
+
+    Linux inode flags section
+
+ */
+#[cfg(target_os = "linux")]
+bitflags! {
+    /**
+       Linux inode flags, as shown by `lsattr` and changed by `chattr`.
+
+       Not every filesystem supports every flag, setting an unsupported flag returns an error.
+       Changing IMMUTABLE and APPEND requires the `CAP_LINUX_IMMUTABLE` capability.
+    */
+    pub struct InodeFlags: u32 {
+        /**
+            The file is compressed by the filesystem. (`chattr +c`)
+        */
+        const COMPRESS = 0x0000_0004;
+
+        /**
+            The file cannot be modified, deleted or renamed. (`chattr +i`)
+        */
+        const IMMUTABLE = 0x0000_0010;
+
+        /**
+            The file can only be opened in append mode for writing. (`chattr +a`)
+        */
+        const APPEND = 0x0000_0020;
+
+        /**
+            The file is skipped by `dump`. (`chattr +d`)
+        */
+        const NODUMP = 0x0000_0040;
+
+        /**
+            The accessed date of the file is not updated. (`chattr +A`)
+        */
+        const NOATIME = 0x0000_0080;
+
+        /**
+            The file is not copy on write. (`chattr +C`, only useful on btrfs.)
+        */
+        const NOCOW = 0x0080_0000;
+
+        /**
+            New files in the directory inherit its project id. (`chattr +P`)
+        */
+        const PROJINHERIT = 0x2000_0000;
+    }
+}
+
+/**
+   Get the inode flags of a file. (Like `lsattr`.)
+
+   Flags that are not part of [`InodeFlags`] are ignored.
+
+   ## Params
+   file: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<[`InodeFlags`]> -> The flags, or an error of kind `Unsupported` if the filesystem has no inode flags.
+
+   ## Examples
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::{get_inode_flags, InodeFlags};
+
+   if let Ok(flags) = get_inode_flags(Path::new("/test.txt")) {
+       println!("Immutable: {}", flags.contains(InodeFlags::IMMUTABLE));
+   }
+   ```
+*/
+#[cfg(target_os = "linux")]
+pub fn get_inode_flags(file: &Path) -> io::Result<InodeFlags> {
+    let handle = open_for_flags(file)?;
+    Ok(InodeFlags::from_bits_truncate(read_inode_flags(&handle)?))
+}
+
+/**
+   Add inode flags to a file, leaving the other flags unchanged. (Like `chattr +`.)
+
+   ## Params
+   file: &Path -> The path to the file. <br>
+   flags: [`InodeFlags`] -> The flags to add. (Bit-wise OR can be used to add multiple flags.)
+
+   ## Returns
+   io::Result<()> -> An error of kind `Unsupported` if the filesystem does not support one of the flags.
+
+   ## Examples
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::{set_inode_flags, InodeFlags};
+
+   set_inode_flags(Path::new("/test.txt"), InodeFlags::NODUMP | InodeFlags::NOATIME);
+   ```
+*/
+#[cfg(target_os = "linux")]
+pub fn set_inode_flags(file: &Path, flags: InodeFlags) -> io::Result<()> {
+    update_inode_flags(file, |bits| bits | flags.bits, flags, InodeFlags::empty())
+}
+
+/**
+   Remove inode flags from a file, leaving the other flags unchanged. (Like `chattr -`.)
+
+   ## Params
+   file: &Path -> The path to the file. <br>
+   flags: [`InodeFlags`] -> The flags to remove. (Bit-wise OR can be used to remove multiple flags.)
+
+   ## Returns
+   io::Result<()> -> An error if the flags could not be removed.
+
+   ## Examples
+   ```rust
+   use std::path::Path;
+   use system_extensions::metadata::attribute::{remove_inode_flags, InodeFlags};
+
+   remove_inode_flags(Path::new("/test.txt"), InodeFlags::IMMUTABLE);
+   ```
+*/
+#[cfg(target_os = "linux")]
+pub fn remove_inode_flags(file: &Path, flags: InodeFlags) -> io::Result<()> {
+    update_inode_flags(file, |bits| bits & !flags.bits, InodeFlags::empty(), flags)
+}
+
+/// Change the raw flags of a file, then check that the filesystem kept the change.
+#[cfg(target_os = "linux")]
+fn update_inode_flags<F: Fn(u32) -> u32>(file: &Path, update: F, added: InodeFlags, removed: InodeFlags) -> io::Result<()> {
+    let handle = open_for_flags(file)?;
+    let current = read_inode_flags(&handle)?;
+    let new = update(current);
+    if new == current {
+        return Ok(());
+    }
+    write_inode_flags(&handle, new)?;
+
+    // Some filesystems silently ignore flags they do not support.
+    let result = InodeFlags::from_bits_truncate(read_inode_flags(&handle)?);
+    let ignored = (added - result) | (removed & result);
+    if !ignored.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported,
+                                  format!("The filesystem does not support the inode flags {:?}", ignored)));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn open_for_flags(file: &Path) -> io::Result<std::fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    // O_NONBLOCK so opening a FIFO does not wait for a writer, the same as chattr.
+    std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(file)
+}
+
+#[cfg(target_os = "linux")]
+fn read_inode_flags(handle: &std::fs::File) -> io::Result<u32> {
+    use std::os::unix::io::AsRawFd;
+
+    // The kernel reads and writes an int, even though the ioctl is defined with a long.
+    let mut bits: libc::c_int = 0;
+    if unsafe { libc::ioctl(handle.as_raw_fd(), libc::FS_IOC_GETFLAGS, &mut bits) } != 0 {
+        return Err(inode_flags_error());
+    }
+    Ok(bits as u32)
+}
+
+#[cfg(target_os = "linux")]
+fn write_inode_flags(handle: &std::fs::File, bits: u32) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let bits = bits as libc::c_int;
+    if unsafe { libc::ioctl(handle.as_raw_fd(), libc::FS_IOC_SETFLAGS, &bits) } != 0 {
+        return Err(inode_flags_error());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn inode_flags_error() -> io::Error {
+    let error = io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ENOTTY) | Some(libc::ENOTSUP) | Some(libc::EINVAL) =>
+            io::Error::new(io::ErrorKind::Unsupported, format!("The filesystem does not support inode flags ({})", error)),
+        _ => error,
+    }
+}
@@ -0,0 +1,242 @@
+#[cfg(windows)]
+extern crate winapi;
+
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::metadata::attribute::Attributes;
+
+/**
+    What the metadata functions can do on the filesystem of a path.
+
+    Obtained using [`capabilities`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataCapabilities {
+    /// The type of the filesystem. (ex: ext4, apfs, NTFS)
+    pub filesystem_type: String,
+    /// If the creation (birth) date can be read with `get_creation_date`.
+    pub birth_time_readable: bool,
+    /// If the creation (birth) date can be set with `set_creation_date`.
+    pub birth_time_settable: bool,
+    /// The smallest difference between two dates the filesystem can store.
+    pub timestamp_resolution: Duration,
+    /// The [`Attributes`] that have an effect on the filesystem.
+    pub attributes: Attributes,
+}
+
+/**
+   Get what the metadata functions can do on the filesystem of a path.
+
+   The timestamp resolution is based on the type of the filesystem and is the
+   resolution of the modified date.
+
+   Support for the attributes stored in extended attributes is checked by writing and removing a
+   scratch `user.` extended attribute, so they are only reported if the path can be written.
+
+   ## Params
+   path: &Path -> A path on the filesystem to check.
+
+   ## Returns
+   io::Result<[`MetadataCapabilities`]> -> The capabilities, or an error if the path could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::capabilities;
+   use std::path::Path;
+
+   let caps = capabilities(Path::new(".")).unwrap();
+   if !caps.birth_time_settable {
+       println!("The creation date cannot be set on {}.", caps.filesystem_type);
+   }
+   ```
+*/
+#[cfg(target_os = "linux")]
+pub fn capabilities(path: &Path) -> io::Result<MetadataCapabilities> {
+    use crate::metadata::time::{path_to_cstring, statx};
+
+    let stx = statx(path, 0, libc::STATX_BTIME)?;
+
+    let c_path = path_to_cstring(path)?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // The magic numbers are from linux/magic.h
+    #[allow(clippy::unnecessary_cast)]
+    let (filesystem_type, resolution) = match stat.f_type as i64 {
+        0xEF53 => ("ext4", Duration::from_nanos(1)),
+        0x5846_5342 => ("xfs", Duration::from_nanos(1)),
+        0x9123_683E => ("btrfs", Duration::from_nanos(1)),
+        0x0102_1994 => ("tmpfs", Duration::from_nanos(1)),
+        0x794C_7630 => ("overlayfs", Duration::from_nanos(1)),
+        0x2FC1_2FC1 => ("zfs", Duration::from_nanos(1)),
+        0xF2F5_2010 => ("f2fs", Duration::from_nanos(1)),
+        0x6969 => ("nfs", Duration::from_nanos(1)),
+        0xFF53_4D42 => ("cifs", Duration::from_nanos(100)),
+        0x7366_746E => ("ntfs", Duration::from_nanos(100)),
+        0x4D44 => ("msdos", Duration::from_secs(2)),
+        0x2011_BAB0 => ("exfat", Duration::from_millis(10)),
+        0x9660 => ("iso9660", Duration::from_secs(1)),
+        0x482B => ("hfsplus", Duration::from_secs(1)),
+        0x6573_5546 => ("fuse", Duration::from_nanos(1)),
+        _ => ("unknown", Duration::from_nanos(1)),
+    };
+
+    Ok(MetadataCapabilities {
+        filesystem_type: filesystem_type.to_string(),
+        birth_time_readable: stx.stx_mask & libc::STATX_BTIME != 0,
+        birth_time_settable: false,
+        timestamp_resolution: resolution,
+        attributes: Attributes::HIDDEN | Attributes::READ_ONLY | dos_attributes(path),
+    })
+}
+
+/**
+   Get what the metadata functions can do on the filesystem of a path.
+
+   The timestamp resolution is based on the type of the filesystem and is the
+   resolution of the modified date.
+
+   Support for the attributes stored in extended attributes is checked by writing and removing a
+   scratch `user.` extended attribute, so they are only reported if the path can be written.
+
+   ## Params
+   path: &Path -> A path on the filesystem to check.
+
+   ## Returns
+   io::Result<[`MetadataCapabilities`]> -> The capabilities, or an error if the path could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::capabilities;
+   use std::path::Path;
+
+   let caps = capabilities(Path::new(".")).unwrap();
+   if !caps.birth_time_settable {
+       println!("The creation date cannot be set on {}.", caps.filesystem_type);
+   }
+   ```
+*/
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn capabilities(path: &Path) -> io::Result<MetadataCapabilities> {
+    use crate::metadata::time::path_to_cstring;
+    use std::ffi::CStr;
+
+    let c_path = path_to_cstring(path)?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let filesystem_type = unsafe { CStr::from_ptr(stat.f_fstypename.as_ptr()) }.to_string_lossy().into_owned();
+
+    let resolution = match filesystem_type.as_str() {
+        "hfs" | "cd9660" => Duration::from_secs(1),
+        "msdos" => Duration::from_secs(2),
+        "exfat" => Duration::from_millis(10),
+        "ntfs" | "smbfs" => Duration::from_nanos(100),
+        _ => Duration::from_nanos(1),
+    };
+
+    Ok(MetadataCapabilities {
+        birth_time_readable: std::fs::metadata(path)?.created().is_ok(),
+        birth_time_settable: cfg!(target_os = "macos") && command_exists("SetFile"),
+        filesystem_type,
+        timestamp_resolution: resolution,
+        attributes: Attributes::HIDDEN | Attributes::READ_ONLY | dos_attributes(path),
+    })
+}
+
+/**
+    The attributes stored in the `user.DOSATTRIB` extended attribute, if `user.` extended attributes can be written.
+
+    Some filesystems can list extended attributes but reject `user.` ones, so this is checked by
+    writing and then removing a scratch attribute on the path.
+*/
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn dos_attributes(path: &Path) -> Attributes {
+    use crate::metadata::xattr;
+    const PROBE: &str = "user.system_extensions.probe";
+
+    if xattr::set(path, PROBE, b"").is_err() {
+        return Attributes::empty();
+    }
+    let _ = xattr::remove(path, PROBE);
+    Attributes::SYSTEM | Attributes::ARCHIVE | Attributes::TEMPORARY | Attributes::OFFLINE
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn dos_attributes(_path: &Path) -> Attributes {
+    Attributes::empty()
+}
+
+/**
+    Check if a command can be found on the PATH.
+*/
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn command_exists(command: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(command).is_file()))
+        .unwrap_or(false)
+}
+
+/**
+   Get what the metadata functions can do on the filesystem of a path.
+
+   The timestamp resolution is based on the type of the filesystem and is the
+   resolution of the modified date.
+
+   ## Params
+   path: &Path -> A path on the filesystem to check.
+
+   ## Returns
+   io::Result<[`MetadataCapabilities`]> -> The capabilities, or an error if the path could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::capabilities;
+   use std::path::Path;
+
+   let caps = capabilities(Path::new(".")).unwrap();
+   println!("Dates are stored every {:?} on {}.", caps.timestamp_resolution, caps.filesystem_type);
+   ```
+*/
+#[cfg(windows)]
+pub fn capabilities(path: &Path) -> io::Result<MetadataCapabilities> {
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use self::winapi::_core::ptr::null_mut;
+    use self::winapi::um::fileapi::{GetVolumePathNameW, GetVolumeInformationW};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(once(0)).collect();
+    let mut root = [0u16; 261];
+    let mut name = [0u16; 261];
+    unsafe {
+        if GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), root.len() as u32) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if GetVolumeInformationW(root.as_ptr(), null_mut(), 0, null_mut(), null_mut(), null_mut(),
+                                 name.as_mut_ptr(), name.len() as u32) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    let length = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+    let filesystem_type = String::from_utf16_lossy(&name[..length]);
+
+    let (birth_time, resolution) = match filesystem_type.as_str() {
+        "FAT" | "FAT32" => (true, Duration::from_secs(2)),
+        "exFAT" => (true, Duration::from_millis(10)),
+        "CDFS" | "UDF" => (false, Duration::from_secs(1)),
+        _ => (true, Duration::from_nanos(100)),
+    };
+
+    Ok(MetadataCapabilities {
+        filesystem_type,
+        birth_time_readable: birth_time,
+        birth_time_settable: birth_time,
+        timestamp_resolution: resolution,
+        attributes: Attributes::all(),
+    })
+}
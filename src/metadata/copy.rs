@@ -0,0 +1,361 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::metadata::time::copy_times;
+
+/**
+    A kind of metadata that [`copy_metadata`] copies.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataItem {
+    /// The accessed and modified dates, and the creation date on Windows.
+    Timestamps,
+    /// The permission bits, including the setuid, setgid and sticky bits. (Unix only.)
+    Permissions,
+    /// The user and group that own the file. (Unix only.)
+    Ownership,
+    /// The extended attributes, except for ACLs. (Linux and Mac only.)
+    Xattrs,
+    /// The access ACL, and the default ACL of directories. (Linux only.)
+    Acl,
+    /// The [`Attributes`](crate::metadata::attribute::Attributes) of the file.
+    /// On Unix `HIDDEN` is part of the name, so it is not copied.
+    Attributes,
+}
+
+impl fmt::Display for MetadataItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MetadataItem::Timestamps => "timestamps",
+            MetadataItem::Permissions => "permissions",
+            MetadataItem::Ownership => "ownership",
+            MetadataItem::Xattrs => "xattrs",
+            MetadataItem::Acl => "ACL",
+            MetadataItem::Attributes => "attributes",
+        })
+    }
+}
+
+/**
+    The metadata that [`copy_metadata`] copies.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::CopyOptions;
+
+    // Like `cp --preserve=mode,timestamps`.
+    let mut options = CopyOptions::none();
+    options.set_permissions(true)
+        .set_timestamps(true);
+    ```
+*/
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    pub(crate) timestamps: bool,
+    pub(crate) permissions: bool,
+    pub(crate) ownership: bool,
+    pub(crate) xattrs: bool,
+    pub(crate) acl: bool,
+    pub(crate) attributes: bool,
+    pub(crate) follow_symlinks: bool,
+}
+
+impl CopyOptions {
+    /**
+        Construct the default options, which copy everything the platform supports. (Like `cp --preserve=all`.)
+        Symbolic links are followed by default.
+    */
+    pub fn new() -> CopyOptions {
+        CopyOptions {
+            timestamps: true,
+            permissions: cfg!(unix),
+            ownership: cfg!(unix),
+            xattrs: cfg!(any(target_os = "linux", target_os = "macos")),
+            acl: cfg!(target_os = "linux"),
+            attributes: true,
+            follow_symlinks: true,
+        }
+    }
+
+    /**
+        Construct options that copy nothing, to enable items one at a time.
+    */
+    pub fn none() -> CopyOptions {
+        CopyOptions {
+            timestamps: false,
+            permissions: false,
+            ownership: false,
+            xattrs: false,
+            acl: false,
+            attributes: false,
+            follow_symlinks: true,
+        }
+    }
+
+    /**
+        Set whether the accessed and modified dates (and the creation date on Windows) are copied.
+    */
+    pub fn set_timestamps(&mut self, timestamps: bool) -> &mut Self {
+        self.timestamps = timestamps;
+        self
+    }
+
+    /**
+        Set whether the permission bits are copied.
+    */
+    pub fn set_permissions(&mut self, permissions: bool) -> &mut Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /**
+        Set whether the owner and group are copied. Changing the owner usually requires root.
+    */
+    pub fn set_ownership(&mut self, ownership: bool) -> &mut Self {
+        self.ownership = ownership;
+        self
+    }
+
+    /**
+        Set whether the extended attributes are copied.
+    */
+    pub fn set_xattrs(&mut self, xattrs: bool) -> &mut Self {
+        self.xattrs = xattrs;
+        self
+    }
+
+    /**
+        Set whether the POSIX ACLs are copied.
+    */
+    pub fn set_acl(&mut self, acl: bool) -> &mut Self {
+        self.acl = acl;
+        self
+    }
+
+    /**
+        Set whether the [`Attributes`](crate::metadata::attribute::Attributes) are copied.
+    */
+    pub fn set_attributes(&mut self, attributes: bool) -> &mut Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /**
+        Set whether symbolic links are followed.
+        When not followed, the metadata of the links themselves is copied, and the items
+        a link cannot have (permissions, ACLs and attributes) are skipped.
+    */
+    pub fn set_follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /**
+        Get the items that are enabled.
+    */
+    pub fn items(&self) -> Vec<MetadataItem> {
+        [
+            (self.timestamps, MetadataItem::Timestamps),
+            (self.permissions, MetadataItem::Permissions),
+            (self.ownership, MetadataItem::Ownership),
+            (self.xattrs, MetadataItem::Xattrs),
+            (self.acl, MetadataItem::Acl),
+            (self.attributes, MetadataItem::Attributes),
+        ].iter().filter(|(enabled, _)| *enabled).map(|(_, item)| *item).collect()
+    }
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        CopyOptions::new()
+    }
+}
+
+/**
+   Copy the metadata of one file to another. (Like `cp --preserve=all` or `rsync -aX`.)
+
+   Every enabled item is copied even if some of them fail, so one unsupported item does not
+   stop the rest from being copied. The timestamps are copied last, so they are not changed
+   by copying the other items. Extended attributes that only the destination has are kept.
+
+   ## Params
+   src: &Path -> The file to copy the metadata from. <br>
+   dst: &Path -> The file to copy the metadata to. <br>
+   options: &[`CopyOptions`] -> The metadata to copy.
+
+   ## Returns
+   Result<(), Vec<([`MetadataItem`], io::Error)>> -> The items that could not be copied along with their errors.
+   An item can fail more than once, such as one error for every extended attribute that could not be copied.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::{copy_metadata, CopyOptions};
+   use std::path::Path;
+
+   if let Err(failures) = copy_metadata(Path::new("./a.txt"), Path::new("./b.txt"), &CopyOptions::new()) {
+       for (item, error) in failures {
+           println!("Could not copy the {}: {}", item, error);
+       }
+   }
+   ```
+*/
+pub fn copy_metadata(src: &Path, dst: &Path, options: &CopyOptions) -> Result<(), Vec<(MetadataItem, io::Error)>> {
+    let mut failures = Vec::new();
+    let meta = match if options.follow_symlinks { std::fs::metadata(src) } else { std::fs::symlink_metadata(src) } {
+        Ok(meta) => meta,
+        // Nothing can be copied without the source, so every enabled item fails.
+        Err(e) => return Err(options.items().into_iter()
+            .map(|item| (item, io::Error::new(e.kind(), e.to_string())))
+            .collect()),
+    };
+    let is_symlink = meta.file_type().is_symlink();
+
+    // The owner is changed first, since changing it clears the setuid and setgid bits.
+    if options.ownership {
+        record(&mut failures, MetadataItem::Ownership, copy_ownership(&meta, dst, options.follow_symlinks));
+    }
+    if options.xattrs {
+        copy_xattrs(src, dst, options.follow_symlinks, &mut failures);
+    }
+    if options.acl && !is_symlink {
+        record(&mut failures, MetadataItem::Acl, copy_acl(src, dst, meta.is_dir()));
+    }
+    if options.attributes && !is_symlink {
+        record(&mut failures, MetadataItem::Attributes, copy_attributes(src, dst, &meta, options.permissions));
+    }
+    // The permissions are copied after the xattrs and ACL, which can need write access to the file.
+    if options.permissions && !is_symlink {
+        record(&mut failures, MetadataItem::Permissions, copy_permissions(&meta, dst));
+    }
+    if options.timestamps {
+        record(&mut failures, MetadataItem::Timestamps, copy_times(src, dst, options.follow_symlinks));
+    }
+
+    if failures.is_empty() { Ok(()) } else { Err(failures) }
+}
+
+fn record(failures: &mut Vec<(MetadataItem, io::Error)>, item: MetadataItem, result: io::Result<()>) {
+    if let Err(error) = result {
+        failures.push((item, error));
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn unsupported(item: MetadataItem) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, format!("Copying the {} is not supported on this platform", item))
+}
+
+#[cfg(unix)]
+fn copy_ownership(meta: &std::fs::Metadata, dst: &Path, follow: bool) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    use crate::metadata::owner::{set_owner, set_owner_nofollow, Owner};
+
+    let current = if follow { std::fs::metadata(dst)? } else { std::fs::symlink_metadata(dst)? };
+    // Only the ids that differ are changed, so a user can copy the group of their own files.
+    let user = if current.uid() != meta.uid() { Some(Owner::Id(meta.uid())) } else { None };
+    let group = if current.gid() != meta.gid() { Some(Owner::Id(meta.gid())) } else { None };
+    if user.is_none() && group.is_none() {
+        return Ok(());
+    }
+    if follow { set_owner(dst, user, group) } else { set_owner_nofollow(dst, user, group) }
+}
+
+#[cfg(not(unix))]
+fn copy_ownership(_meta: &std::fs::Metadata, _dst: &Path, _follow: bool) -> io::Result<()> {
+    Err(unsupported(MetadataItem::Ownership))
+}
+
+#[cfg(unix)]
+fn copy_permissions(meta: &std::fs::Metadata, dst: &Path) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    use crate::metadata::permissions::{set_mode, Mode};
+
+    set_mode(dst, Mode::from_bits_truncate(meta.mode()))
+}
+
+#[cfg(not(unix))]
+fn copy_permissions(_meta: &std::fs::Metadata, _dst: &Path) -> io::Result<()> {
+    Err(unsupported(MetadataItem::Permissions))
+}
+
+/// Copy every extended attribute, recording a failure for each one that could not be copied.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn copy_xattrs(src: &Path, dst: &Path, follow: bool, failures: &mut Vec<(MetadataItem, io::Error)>) {
+    use crate::metadata::xattr::{self, XattrError};
+
+    let names = match if follow { xattr::list(src) } else { xattr::list_nofollow(src) } {
+        Ok(names) => names,
+        // A filesystem without xattrs has none to copy.
+        Err(XattrError::NotSupported) => return,
+        Err(e) => return failures.push((MetadataItem::Xattrs, e.into())),
+    };
+    // ACLs are copied with their own item.
+    for name in names.iter().filter(|name| !name.starts_with("system.posix_acl_")) {
+        let result = if follow {
+            xattr::get(src, name).and_then(|value| xattr::set(dst, name, &value))
+        } else {
+            xattr::get_nofollow(src, name).and_then(|value| xattr::set_nofollow(dst, name, &value))
+        };
+        if let Err(e) = result {
+            let error = io::Error::from(e);
+            failures.push((MetadataItem::Xattrs, io::Error::new(error.kind(), format!("{}: {}", name, error))));
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn copy_xattrs(_src: &Path, _dst: &Path, _follow: bool, failures: &mut Vec<(MetadataItem, io::Error)>) {
+    failures.push((MetadataItem::Xattrs, unsupported(MetadataItem::Xattrs)));
+}
+
+#[cfg(target_os = "linux")]
+fn copy_acl(src: &Path, dst: &Path, is_dir: bool) -> io::Result<()> {
+    use crate::metadata::acl::{get_acl, get_default_acl, remove_acl, remove_default_acl, set_acl, set_default_acl, AclTag};
+
+    let acl = get_acl(src)?;
+    // Only an ACL with a mask has entries beyond the permission bits.
+    if acl.get(AclTag::Mask).is_some() {
+        set_acl(dst, &acl)?;
+    } else {
+        remove_acl(dst)?;
+    }
+    if is_dir {
+        match get_default_acl(src)? {
+            Some(default) => set_default_acl(dst, &default)?,
+            None => remove_default_acl(dst)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn copy_acl(_src: &Path, _dst: &Path, _is_dir: bool) -> io::Result<()> {
+    Err(unsupported(MetadataItem::Acl))
+}
+
+#[cfg(unix)]
+fn copy_attributes(src: &Path, dst: &Path, meta: &std::fs::Metadata, permissions: bool) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    use crate::metadata::attribute::{read_dos_attributes, set_read_only, update_dos_attributes, DOS_ATTRIBUTES};
+
+    let dos = read_dos_attributes(src)? & DOS_ATTRIBUTES.bits();
+    // Nothing is written (or required of the filesystem) when neither file has DOS attributes.
+    update_dos_attributes(dst, |bits| (bits & !DOS_ATTRIBUTES.bits()) | dos)?;
+    // READ_ONLY is part of the permission bits, which are copied exactly when enabled.
+    if !permissions {
+        set_read_only(dst, meta.mode() & 0o222 == 0)?;
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn copy_attributes(src: &Path, dst: &Path, _meta: &std::fs::Metadata, _permissions: bool) -> io::Result<()> {
+    use crate::metadata::attribute::{get_attributes, set_attributes_exact, Attributes};
+
+    let attributes = get_attributes(src)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+        .into_iter()
+        .fold(Attributes::empty(), |all, attribute| all | attribute);
+    set_attributes_exact(dst, attributes)
+}
@@ -0,0 +1,435 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::metadata::xattr::{self, XattrError};
+
+bitflags! {
+    /**
+        A set of Linux capabilities. (See `man 7 capabilities`.)
+
+        # Examples
+        ```rust
+        use system_extensions::metadata::file_capabilities::Capabilities;
+
+        let caps = Capabilities::NET_BIND_SERVICE | Capabilities::NET_RAW;
+        assert_eq!(Capabilities::from_name("cap_net_raw"), Some(Capabilities::NET_RAW));
+        assert_eq!(Capabilities::NET_RAW.names(), vec!["cap_net_raw"]);
+        ```
+    */
+    pub struct Capabilities: u64 {
+        const CHOWN = 1 << 0;
+        const DAC_OVERRIDE = 1 << 1;
+        const DAC_READ_SEARCH = 1 << 2;
+        const FOWNER = 1 << 3;
+        const FSETID = 1 << 4;
+        const KILL = 1 << 5;
+        const SETGID = 1 << 6;
+        const SETUID = 1 << 7;
+        const SETPCAP = 1 << 8;
+        const LINUX_IMMUTABLE = 1 << 9;
+        const NET_BIND_SERVICE = 1 << 10;
+        const NET_BROADCAST = 1 << 11;
+        const NET_ADMIN = 1 << 12;
+        const NET_RAW = 1 << 13;
+        const IPC_LOCK = 1 << 14;
+        const IPC_OWNER = 1 << 15;
+        const SYS_MODULE = 1 << 16;
+        const SYS_RAWIO = 1 << 17;
+        const SYS_CHROOT = 1 << 18;
+        const SYS_PTRACE = 1 << 19;
+        const SYS_PACCT = 1 << 20;
+        const SYS_ADMIN = 1 << 21;
+        const SYS_BOOT = 1 << 22;
+        const SYS_NICE = 1 << 23;
+        const SYS_RESOURCE = 1 << 24;
+        const SYS_TIME = 1 << 25;
+        const SYS_TTY_CONFIG = 1 << 26;
+        const MKNOD = 1 << 27;
+        const LEASE = 1 << 28;
+        const AUDIT_WRITE = 1 << 29;
+        const AUDIT_CONTROL = 1 << 30;
+        const SETFCAP = 1 << 31;
+        const MAC_OVERRIDE = 1 << 32;
+        const MAC_ADMIN = 1 << 33;
+        const SYSLOG = 1 << 34;
+        const WAKE_ALARM = 1 << 35;
+        const BLOCK_SUSPEND = 1 << 36;
+        const AUDIT_READ = 1 << 37;
+        const PERFMON = 1 << 38;
+        const BPF = 1 << 39;
+        const CHECKPOINT_RESTORE = 1 << 40;
+    }
+}
+
+// The names of the capabilities, in the order of their numbers.
+const CAPABILITY_NAMES: [&str; 41] = ["cap_chown", "cap_dac_override", "cap_dac_read_search", "cap_fowner",
+    "cap_fsetid", "cap_kill", "cap_setgid", "cap_setuid", "cap_setpcap", "cap_linux_immutable",
+    "cap_net_bind_service", "cap_net_broadcast", "cap_net_admin", "cap_net_raw", "cap_ipc_lock",
+    "cap_ipc_owner", "cap_sys_module", "cap_sys_rawio", "cap_sys_chroot", "cap_sys_ptrace", "cap_sys_pacct",
+    "cap_sys_admin", "cap_sys_boot", "cap_sys_nice", "cap_sys_resource", "cap_sys_time", "cap_sys_tty_config",
+    "cap_mknod", "cap_lease", "cap_audit_write", "cap_audit_control", "cap_setfcap", "cap_mac_override",
+    "cap_mac_admin", "cap_syslog", "cap_wake_alarm", "cap_block_suspend", "cap_audit_read", "cap_perfmon",
+    "cap_bpf", "cap_checkpoint_restore"];
+
+impl Capabilities {
+    /**
+        Get a capability by its name (ex: `cap_net_bind_service`) or number. The name is not case sensitive.
+    */
+    pub fn from_name(name: &str) -> Option<Capabilities> {
+        let name = name.to_ascii_lowercase();
+        let number = match CAPABILITY_NAMES.iter().position(|n| *n == name) {
+            Some(number) => number,
+            None => name.parse::<usize>().ok().filter(|n| *n < CAPABILITY_NAMES.len())?,
+        };
+        Some(Capabilities { bits: 1 << number })
+    }
+
+    /**
+        Get the names of the capabilities in the set, in the order of their numbers.
+    */
+    pub fn names(self) -> Vec<&'static str> {
+        CAPABILITY_NAMES.iter().enumerate()
+            .filter(|(number, _)| self.bits & (1 << number) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::empty()
+    }
+}
+
+/// The revision of the `security.capability` xattr format, in the top byte of the first word.
+const VFS_CAP_REVISION_MASK: u32 = 0xFF00_0000;
+const VFS_CAP_REVISION_1: u32 = 0x0100_0000;
+const VFS_CAP_REVISION_2: u32 = 0x0200_0000;
+const VFS_CAP_REVISION_3: u32 = 0x0300_0000;
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x1;
+
+const CAPABILITY_XATTR: &str = "security.capability";
+
+/**
+    The capabilities of an executable file. (Like `getcap`.)
+
+    When the file is executed, the permitted capabilities are granted, and the inheritable ones are
+    granted if the process already has them as inheritable. If `effective` is set, the granted
+    capabilities are also made effective, which is needed for programs that are not capability aware.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::file_capabilities::{FileCapabilities, Capabilities};
+
+    let caps: FileCapabilities = "cap_net_bind_service=ep".parse().unwrap();
+    assert!(caps.effective_set().contains(Capabilities::NET_BIND_SERVICE));
+    assert_eq!(caps.to_string(), "cap_net_bind_service=ep");
+    ```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileCapabilities {
+    /// The capabilities granted when the file is executed.
+    pub permitted: Capabilities,
+    /// The capabilities granted if the process already has them as inheritable.
+    pub inheritable: Capabilities,
+    /// If the granted capabilities are made effective.
+    pub effective: bool,
+    /// The root user of the user namespace the capabilities apply in, or None for every namespace. (Version 3 only.)
+    pub root_id: Option<u32>,
+}
+
+impl FileCapabilities {
+    /**
+        Construct an empty capability set.
+    */
+    pub fn new() -> FileCapabilities {
+        FileCapabilities::default()
+    }
+
+    /**
+        Get the capabilities that are made effective, which is every permitted and inheritable
+        capability if `effective` is set, otherwise none.
+    */
+    pub fn effective_set(&self) -> Capabilities {
+        if self.effective { self.permitted | self.inheritable } else { Capabilities::empty() }
+    }
+
+    /**
+        Decode the `security.capability` xattr. Versions 1, 2 and 3 are supported.
+
+        # Params
+        bytes: &[u8] -> The value of the xattr.<br>
+
+        # Returns
+        io::Result<FileCapabilities> -> The capabilities, or an error of kind `InvalidData` if the value is not valid.
+    */
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<FileCapabilities> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid security.capability xattr");
+        let word = |index: usize| u32::from_le_bytes([bytes[index * 4], bytes[index * 4 + 1], bytes[index * 4 + 2], bytes[index * 4 + 3]]);
+        if bytes.len() < 4 {
+            return Err(invalid());
+        }
+
+        let magic = word(0);
+        let (permitted, inheritable, root_id) = match (magic & VFS_CAP_REVISION_MASK, bytes.len()) {
+            (VFS_CAP_REVISION_1, 12) => (word(1) as u64, word(2) as u64, None),
+            (VFS_CAP_REVISION_2, 20) =>
+                (word(1) as u64 | (word(3) as u64) << 32, word(2) as u64 | (word(4) as u64) << 32, None),
+            (VFS_CAP_REVISION_3, 24) =>
+                (word(1) as u64 | (word(3) as u64) << 32, word(2) as u64 | (word(4) as u64) << 32, Some(word(5))),
+            _ => return Err(invalid()),
+        };
+        Ok(FileCapabilities {
+            permitted: Capabilities::from_bits_truncate(permitted),
+            inheritable: Capabilities::from_bits_truncate(inheritable),
+            effective: magic & VFS_CAP_FLAGS_EFFECTIVE != 0,
+            root_id,
+        })
+    }
+
+    /**
+        Encode the capabilities in the `security.capability` xattr format.
+        Version 3 is used if a root id is set, otherwise version 2.
+
+        # Returns
+        Vec<u8> -> The value of the xattr.
+    */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let revision = if self.root_id.is_some() { VFS_CAP_REVISION_3 } else { VFS_CAP_REVISION_2 };
+        let magic = revision | if self.effective { VFS_CAP_FLAGS_EFFECTIVE } else { 0 };
+        let mut words = vec![magic,
+                             self.permitted.bits as u32, self.inheritable.bits as u32,
+                             (self.permitted.bits >> 32) as u32, (self.inheritable.bits >> 32) as u32];
+        words.extend(self.root_id);
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    /**
+        Parse capabilities in the `setcap` and `getcap` text format.
+
+        The text is made of clauses separated by spaces. Each clause is a comma separated list of
+        capability names (`all` or nothing for every capability) followed by one or more operators:
+        `=` to replace, `+` to add or `-` to remove the flags that follow. The flags are `e` (effective),
+        `i` (inheritable) and `p` (permitted). A `[rootid=N]` clause sets the root id.
+
+        Files only have one effective flag, so the effective capabilities must be none or all of the
+        permitted and inheritable ones.
+
+        # Params
+        text: &str -> The capabilities text. (ex: `cap_net_raw,cap_net_admin+ep`)<br>
+
+        # Returns
+        io::Result<FileCapabilities> -> The capabilities, or an error of kind `InvalidInput` if the text is invalid.
+    */
+    pub fn parse(text: &str) -> io::Result<FileCapabilities> {
+        let mut sets = [Capabilities::empty(); 3];
+        let mut root_id = None;
+        for clause in text.split_whitespace() {
+            if let Some(id) = clause.strip_prefix("[rootid=").and_then(|c| c.strip_suffix(']')) {
+                root_id = Some(id.parse().map_err(|_| invalid_input(format!("Invalid root id '{}'", id)))?);
+                continue;
+            }
+            let split = clause.find(['=', '+', '-'])
+                .ok_or_else(|| invalid_input(format!("The clause '{}' has no operator", clause)))?;
+            let caps = parse_names(&clause[..split])?;
+
+            let mut operator = None;
+            let mut flags = [false; 3];
+            for c in clause[split..].chars().chain(std::iter::once('\0')) {
+                match c {
+                    '=' | '+' | '-' | '\0' => {
+                        if let Some(operator) = operator {
+                            apply(&mut sets, caps, operator, flags);
+                        }
+                        operator = Some(c);
+                        flags = [false; 3];
+                    }
+                    'e' | 'E' => flags[0] = true,
+                    'i' | 'I' => flags[1] = true,
+                    'p' | 'P' => flags[2] = true,
+                    _ => return Err(invalid_input(format!("Unknown flag '{}' in '{}'", c, clause))),
+                }
+            }
+        }
+
+        let [effective, inheritable, permitted] = sets;
+        if !effective.is_empty() && effective != permitted | inheritable {
+            return Err(invalid_input(
+                "The effective flag must be set for all or none of the permitted and inheritable capabilities".to_string()));
+        }
+        Ok(FileCapabilities { permitted, inheritable, effective: !effective.is_empty(), root_id })
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Parse the capability list of a clause, where an empty list or `all` is every capability.
+fn parse_names(names: &str) -> io::Result<Capabilities> {
+    if names.is_empty() || names.eq_ignore_ascii_case("all") {
+        return Ok(Capabilities::all());
+    }
+    names.split(',').try_fold(Capabilities::empty(), |caps, name| {
+        Capabilities::from_name(name)
+            .map(|cap| caps | cap)
+            .ok_or_else(|| invalid_input(format!("Unknown capability '{}'", name)))
+    })
+}
+
+/// Apply one operator of a clause to the effective, inheritable and permitted sets.
+fn apply(sets: &mut [Capabilities; 3], caps: Capabilities, operator: char, flags: [bool; 3]) {
+    for (set, flag) in sets.iter_mut().zip(flags) {
+        match operator {
+            '=' => set.set(caps, flag),
+            '+' if flag => set.insert(caps),
+            '-' if flag => set.remove(caps),
+            _ => {}
+        }
+    }
+}
+
+/// Format the flags of a capability, in the `eip` order `getcap` uses.
+fn flag_letters(flags: [bool; 3]) -> String {
+    flags.iter().zip(['e', 'i', 'p']).filter(|(set, _)| **set).map(|(_, c)| c).collect()
+}
+
+impl fmt::Display for FileCapabilities {
+    /**
+        Formats the capabilities in the `getcap` text format. (ex: `cap_net_bind_service=ep`)
+
+        The flags most capabilities share are written first as `=flags`, when that is shorter,
+        and the other capabilities are written relative to them.
+    */
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let effective = self.effective_set();
+        let flags_of = |cap: Capabilities| [effective.contains(cap), self.inheritable.contains(cap), self.permitted.contains(cap)];
+
+        // Group the capabilities by their flags.
+        let mut groups: Vec<([bool; 3], Capabilities)> = Vec::new();
+        for number in 0..CAPABILITY_NAMES.len() {
+            let cap = Capabilities { bits: 1 << number };
+            let flags = flags_of(cap);
+            match groups.iter_mut().find(|(f, _)| *f == flags) {
+                Some((_, caps)) => caps.insert(cap),
+                None => groups.push((flags, cap)),
+            }
+        }
+        let base = groups.iter()
+            .filter(|(flags, caps)| *flags != [false; 3] && caps.bits.count_ones() as usize * 2 > CAPABILITY_NAMES.len())
+            .map(|(flags, _)| *flags)
+            .next()
+            .unwrap_or([false; 3]);
+
+        let mut clauses = Vec::new();
+        if base != [false; 3] {
+            clauses.push(format!("={}", flag_letters(base)));
+        }
+        for (flags, caps) in groups.iter().filter(|(flags, _)| *flags != base) {
+            let added = [flags[0] && !base[0], flags[1] && !base[1], flags[2] && !base[2]];
+            let removed = [base[0] && !flags[0], base[1] && !flags[1], base[2] && !flags[2]];
+            let operation = if base == [false; 3] {
+                format!("={}", flag_letters(*flags))
+            } else if removed == [false; 3] {
+                format!("+{}", flag_letters(added))
+            } else if added == [false; 3] {
+                format!("-{}", flag_letters(removed))
+            } else {
+                format!("={}", flag_letters(*flags))
+            };
+            clauses.push(format!("{}{}", caps.names().join(","), operation));
+        }
+        if clauses.is_empty() {
+            clauses.push("=".to_string());
+        }
+        if let Some(root_id) = self.root_id {
+            clauses.push(format!("[rootid={}]", root_id));
+        }
+        write!(f, "{}", clauses.join(" "))
+    }
+}
+
+impl FromStr for FileCapabilities {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FileCapabilities::parse(s)
+    }
+}
+
+fn xattr_error(error: XattrError) -> io::Error {
+    match error {
+        XattrError::NotSupported => io::Error::new(io::ErrorKind::Unsupported, "The filesystem does not support file capabilities"),
+        error => error.into(),
+    }
+}
+
+/**
+   Get the capabilities of a file. (Like `getcap`.)
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<Option<[`FileCapabilities`]>> -> The capabilities, or None if the file has none.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::file_capabilities::{get_capabilities, Capabilities};
+   use std::path::Path;
+
+   let granted = match get_capabilities(Path::new("/usr/bin/ping")) {
+       Ok(Some(caps)) => caps.effective_set().contains(Capabilities::NET_RAW),
+       _ => false,
+   };
+   ```
+*/
+pub fn get_capabilities(path: &Path) -> io::Result<Option<FileCapabilities>> {
+    match xattr::get(path, CAPABILITY_XATTR) {
+        Ok(bytes) => FileCapabilities::from_bytes(&bytes).map(Some),
+        Err(XattrError::NoData) | Err(XattrError::NotSupported) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/**
+   Set the capabilities of a file. (Like `setcap`.)
+   This requires the `cap_setfcap` capability, usually by running as root.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   caps: &[`FileCapabilities`] -> The new capabilities.
+
+   ## Returns
+   io::Result<()> -> An error if the capabilities could not be set.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::file_capabilities::set_capabilities;
+   use std::path::Path;
+
+   // Allow the server to listen on port 80 without running as root.
+   set_capabilities(Path::new("/opt/server/bin/server"), &"cap_net_bind_service=ep".parse().unwrap());
+   ```
+*/
+pub fn set_capabilities(path: &Path, caps: &FileCapabilities) -> io::Result<()> {
+    xattr::set(path, CAPABILITY_XATTR, &caps.to_bytes()).map_err(xattr_error)
+}
+
+/**
+   Remove the capabilities of a file. (Like `setcap -r`.)
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<()> -> An error if the capabilities could not be removed. Nothing happens if the file has none.
+*/
+pub fn remove_capabilities(path: &Path) -> io::Result<()> {
+    match xattr::remove(path, CAPABILITY_XATTR) {
+        Ok(()) | Err(XattrError::NoData) => Ok(()),
+        Err(e) => Err(xattr_error(e)),
+    }
+}
@@ -0,0 +1,326 @@
+#[cfg(windows)]
+extern crate winapi;
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/**
+    Information about the filesystem a path is on.
+
+    Obtained using [`filesystem_of`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilesystemInfo {
+    /// The directory the filesystem is mounted on. (ex: `/`, `/home` or `C:\`)
+    pub mount_point: PathBuf,
+    /// The device or source of the filesystem. (ex: `/dev/sda1`, `tmpfs` or `\\?\Volume{...}\`)
+    pub source: String,
+    /// The type of the filesystem. (ex: ext4, apfs, NTFS)
+    pub filesystem_type: String,
+    /// The options the filesystem is mounted with. (ex: `rw`, `noatime`) Empty on Windows.
+    pub mount_options: Vec<String>,
+    /// The size of the filesystem in bytes.
+    pub total_bytes: u64,
+    /// The free space in bytes, including the space reserved for root.
+    pub free_bytes: u64,
+    /// The free space in bytes that the current user can write to.
+    pub available_bytes: u64,
+    /// The number of inodes. (0 if the filesystem does not have a fixed number, and on Windows.)
+    pub total_inodes: u64,
+    /// The number of free inodes.
+    pub free_inodes: u64,
+    /// The block size of the filesystem in bytes.
+    pub block_size: u64,
+    /// If the filesystem is mounted read-only.
+    pub read_only: bool,
+}
+
+impl FilesystemInfo {
+    /**
+        Get the space in use in bytes.
+    */
+    pub fn used_bytes(&self) -> u64 {
+        self.total_bytes.saturating_sub(self.free_bytes)
+    }
+
+    /**
+        Get the number of inodes in use.
+    */
+    pub fn used_inodes(&self) -> u64 {
+        self.total_inodes.saturating_sub(self.free_inodes)
+    }
+}
+
+/**
+   Get information about the filesystem a path is on. (Like `df` and `findmnt`.)
+
+   The space and inode usage come from `statvfs`, and the mount point, type and options come
+   from `/proc/self/mountinfo`.
+
+   ## Params
+   path: &Path -> A path on the filesystem.
+
+   ## Returns
+   io::Result<[`FilesystemInfo`]> -> The information, or an error if the path could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::filesystem_of;
+   use std::path::Path;
+
+   let fs = filesystem_of(Path::new(".")).unwrap();
+   if fs.available_bytes < 1024 * 1024 * 1024 {
+       println!("Less than 1 GiB is free on {} ({}).", fs.mount_point.display(), fs.filesystem_type);
+   }
+   ```
+*/
+#[cfg(target_os = "linux")]
+pub fn filesystem_of(path: &Path) -> io::Result<FilesystemInfo> {
+    use std::os::unix::fs::MetadataExt;
+    use crate::metadata::time::path_to_cstring;
+
+    let path = std::fs::canonicalize(path)?;
+    let dev = std::fs::metadata(&path)?.dev();
+    let device = (libc::major(dev), libc::minor(dev));
+
+    let c_path = path_to_cstring(&path)?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mountinfo = std::fs::read_to_string("/proc/self/mountinfo")?;
+    let mounts: Vec<Mount> = mountinfo.lines().filter_map(Mount::parse).collect();
+    // The deepest mount containing the path is the one it is on. Mounts on the same directory
+    // hide the earlier ones, and the device is checked first so bind mounts do not confuse it.
+    let containing = |check_device: bool| mounts.iter()
+        .filter(|m| path.starts_with(&m.mount_point) && (!check_device || m.device == device))
+        .max_by_key(|m| m.mount_point.components().count());
+    let mount = containing(true).or_else(|| containing(false))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("No mount contains {}", path.display())))?;
+
+    let mut mount_options = mount.options.clone();
+    for option in &mount.super_options {
+        if !mount_options.contains(option) {
+            mount_options.push(option.clone());
+        }
+    }
+
+    let fragment = stat.f_frsize as u64;
+    Ok(FilesystemInfo {
+        mount_point: mount.mount_point.clone(),
+        source: mount.source.clone(),
+        filesystem_type: mount.filesystem_type.clone(),
+        mount_options,
+        total_bytes: stat.f_blocks as u64 * fragment,
+        free_bytes: stat.f_bfree as u64 * fragment,
+        available_bytes: stat.f_bavail as u64 * fragment,
+        total_inodes: stat.f_files as u64,
+        free_inodes: stat.f_ffree as u64,
+        block_size: stat.f_bsize as u64,
+        read_only: stat.f_flag & libc::ST_RDONLY != 0,
+    })
+}
+
+/**
+    A line of `/proc/self/mountinfo`. (See `man 5 proc`.)
+*/
+#[cfg(target_os = "linux")]
+struct Mount {
+    device: (u32, u32),
+    mount_point: PathBuf,
+    options: Vec<String>,
+    filesystem_type: String,
+    source: String,
+    super_options: Vec<String>,
+}
+
+#[cfg(target_os = "linux")]
+impl Mount {
+    /**
+        Parse a line, which looks like:
+        `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue`
+    */
+    fn parse(line: &str) -> Option<Mount> {
+        let (mount, filesystem) = line.split_once(" - ")?;
+        let mount: Vec<&str> = mount.split(' ').collect();
+        let filesystem: Vec<&str> = filesystem.split(' ').collect();
+        if mount.len() < 6 || filesystem.len() < 3 {
+            return None;
+        }
+        let (major, minor) = mount[2].split_once(':')?;
+        let options = |text: &str| text.split(',').map(unescape).collect();
+        Some(Mount {
+            device: (major.parse().ok()?, minor.parse().ok()?),
+            mount_point: PathBuf::from(unescape(mount[4])),
+            options: options(mount[5]),
+            filesystem_type: unescape(filesystem[0]),
+            source: unescape(filesystem[1]),
+            super_options: options(filesystem[2]),
+        })
+    }
+}
+
+/**
+    Replace the octal escapes (ex: `\040` for a space) the kernel writes in mountinfo.
+*/
+#[cfg(target_os = "linux")]
+fn unescape(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4)
+            .filter(|_| bytes[i] == b'\\')
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match octal {
+            Some(byte) => {
+                output.push(byte);
+                i += 4;
+            }
+            None => {
+                output.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/**
+   Get information about the filesystem a path is on. (Like `df`.)
+
+   All the information comes from `statfs`. The mount options are derived from the mount flags.
+
+   ## Params
+   path: &Path -> A path on the filesystem.
+
+   ## Returns
+   io::Result<[`FilesystemInfo`]> -> The information, or an error if the path could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::filesystem_of;
+   use std::path::Path;
+
+   let fs = filesystem_of(Path::new(".")).unwrap();
+   println!("{} of {} bytes are free.", fs.available_bytes, fs.total_bytes);
+   ```
+*/
+#[cfg(all(unix, not(target_os = "linux")))]
+#[allow(clippy::unnecessary_cast)]
+pub fn filesystem_of(path: &Path) -> io::Result<FilesystemInfo> {
+    use crate::metadata::time::path_to_cstring;
+    use std::ffi::CStr;
+
+    let c_path = path_to_cstring(path)?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let text = |chars: &[libc::c_char]| unsafe { CStr::from_ptr(chars.as_ptr()) }.to_string_lossy().into_owned();
+
+    // The field types differ between the BSDs, so everything is cast.
+    let flags = stat.f_flags as u64;
+    let read_only = flags & libc::MNT_RDONLY as u64 != 0;
+    let mut mount_options = vec![if read_only { "ro" } else { "rw" }.to_string()];
+    for (flag, name) in [(libc::MNT_NOSUID, "nosuid"), (libc::MNT_NODEV, "nodev"), (libc::MNT_NOEXEC, "noexec"),
+                         (libc::MNT_SYNCHRONOUS, "sync"), (libc::MNT_NOATIME, "noatime")] {
+        if flags & flag as u64 != 0 {
+            mount_options.push(name.to_string());
+        }
+    }
+
+    let block_size = stat.f_bsize as u64;
+    Ok(FilesystemInfo {
+        mount_point: PathBuf::from(text(&stat.f_mntonname)),
+        source: text(&stat.f_mntfromname),
+        filesystem_type: text(&stat.f_fstypename),
+        mount_options,
+        total_bytes: stat.f_blocks as u64 * block_size,
+        free_bytes: stat.f_bfree as u64 * block_size,
+        // Can be negative on the BSDs when root has used the reserved space.
+        available_bytes: (stat.f_bavail as i64).max(0) as u64 * block_size,
+        total_inodes: stat.f_files as u64,
+        free_inodes: (stat.f_ffree as i64).max(0) as u64,
+        block_size,
+        read_only,
+    })
+}
+
+/**
+   Get information about the filesystem a path is on.
+
+   Windows does not have inodes or mount options, so those are 0 and empty.
+
+   ## Params
+   path: &Path -> A path on the filesystem.
+
+   ## Returns
+   io::Result<[`FilesystemInfo`]> -> The information, or an error if the path could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::filesystem_of;
+   use std::path::Path;
+
+   let fs = filesystem_of(Path::new(".")).unwrap();
+   println!("{} of {} bytes are free on {}.", fs.available_bytes, fs.total_bytes, fs.mount_point.display());
+   ```
+*/
+#[cfg(windows)]
+pub fn filesystem_of(path: &Path) -> io::Result<FilesystemInfo> {
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use self::winapi::_core::ptr::null_mut;
+    use self::winapi::um::fileapi::{GetDiskFreeSpaceExW, GetDiskFreeSpaceW, GetVolumeInformationW,
+                                     GetVolumeNameForVolumeMountPointW, GetVolumePathNameW};
+    use self::winapi::um::winnt::{FILE_READ_ONLY_VOLUME, ULARGE_INTEGER};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(once(0)).collect();
+    let mut root = [0u16; 261];
+    let mut volume = [0u16; 50];
+    let mut name = [0u16; 261];
+    let mut flags = 0u32;
+    let (mut available, mut total, mut free): (ULARGE_INTEGER, ULARGE_INTEGER, ULARGE_INTEGER) =
+        unsafe { (std::mem::zeroed(), std::mem::zeroed(), std::mem::zeroed()) };
+    let (mut sectors_per_cluster, mut bytes_per_sector, mut free_clusters, mut clusters) = (0u32, 0u32, 0u32, 0u32);
+    unsafe {
+        if GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), root.len() as u32) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if GetVolumeInformationW(root.as_ptr(), null_mut(), 0, null_mut(), null_mut(), &mut flags,
+                                 name.as_mut_ptr(), name.len() as u32) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if GetDiskFreeSpaceExW(root.as_ptr(), &mut available, &mut total, &mut free) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if GetDiskFreeSpaceW(root.as_ptr(), &mut sectors_per_cluster, &mut bytes_per_sector,
+                             &mut free_clusters, &mut clusters) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Network shares do not have a volume name.
+        if GetVolumeNameForVolumeMountPointW(root.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) == 0 {
+            volume[0] = 0;
+        }
+    }
+    let to_string = |wide: &[u16]| {
+        let length = wide.iter().position(|c| *c == 0).unwrap_or(wide.len());
+        String::from_utf16_lossy(&wide[..length])
+    };
+
+    Ok(FilesystemInfo {
+        mount_point: PathBuf::from(to_string(&root)),
+        source: to_string(&volume),
+        filesystem_type: to_string(&name),
+        mount_options: Vec::new(),
+        total_bytes: unsafe { *total.QuadPart() },
+        free_bytes: unsafe { *free.QuadPart() },
+        available_bytes: unsafe { *available.QuadPart() },
+        total_inodes: 0,
+        free_inodes: 0,
+        block_size: sectors_per_cluster as u64 * bytes_per_sector as u64,
+        read_only: flags & FILE_READ_ONLY_VOLUME != 0,
+    })
+}
@@ -0,0 +1,212 @@
+use std::io;
+use std::path::Path;
+
+use crate::metadata::attribute::{read_dos_attributes, Attributes};
+use crate::metadata::permissions::Mode;
+use crate::metadata::time::{timestamp_to_local_filetime, FileTime};
+
+/**
+    The type of a file.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    /// A regular file.
+    File,
+    /// A directory.
+    Directory,
+    /// A symbolic link. (Only returned by [`inspect_nofollow`].)
+    Symlink,
+    /// A named pipe.
+    Fifo,
+    /// A Unix domain socket.
+    Socket,
+    /// A block device. (ex: `/dev/sda`)
+    BlockDevice,
+    /// A character device. (ex: `/dev/null`)
+    CharDevice,
+}
+
+impl FileKind {
+    // mode_t is a u16 on some platforms.
+    #[allow(clippy::unnecessary_cast)]
+    fn from_mode(mode: u32) -> FileKind {
+        match mode & libc::S_IFMT as u32 {
+            m if m == libc::S_IFDIR as u32 => FileKind::Directory,
+            m if m == libc::S_IFLNK as u32 => FileKind::Symlink,
+            m if m == libc::S_IFIFO as u32 => FileKind::Fifo,
+            m if m == libc::S_IFSOCK as u32 => FileKind::Socket,
+            m if m == libc::S_IFBLK as u32 => FileKind::BlockDevice,
+            m if m == libc::S_IFCHR as u32 => FileKind::CharDevice,
+            _ => FileKind::File,
+        }
+    }
+}
+
+/**
+    Everything the metadata module knows about a file, read at once with [`inspect`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// The type of the file.
+    pub kind: FileKind,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The space allocated for the file, in 512 byte blocks.
+    pub blocks: u64,
+    /// The permission bits of the file.
+    pub mode: Mode,
+    /// The attributes of the file.
+    pub attributes: Attributes,
+    /// The id of the user that owns the file.
+    pub uid: u32,
+    /// The id of the group that owns the file.
+    pub gid: u32,
+    /// The inode number of the file.
+    pub inode: u64,
+    /// The major and minor number of the device the file is on.
+    pub device: (u32, u32),
+    /// The major and minor number of the device the file is, for block and character devices.
+    pub raw_device: Option<(u32, u32)>,
+    /// The amount of hard links to the file.
+    pub links: u64,
+    /// When the file was last accessed.
+    pub accessed: FileTime,
+    /// When the contents of the file were last modified.
+    pub modified: FileTime,
+    /// When the metadata of the file was last changed.
+    pub changed: FileTime,
+    /// When the file was created, or None if the filesystem does not store it.
+    pub created: Option<FileTime>,
+}
+
+impl FileMetadata {
+    /**
+        Get the space allocated for the file in bytes.
+    */
+    pub fn allocated_bytes(&self) -> u64 {
+        self.blocks * 512
+    }
+}
+
+/**
+   Get all the metadata of a file at once.
+
+   On Linux everything except the DOS attributes comes from a single `statx` call,
+   so the file does not have to be opened or readable.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<[`FileMetadata`]> -> The metadata of the file.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::inspect;
+   use std::path::Path;
+
+   let meta = inspect(Path::new("/")).unwrap();
+   println!("{} {} {}", meta.mode, meta.uid, meta.modified);
+   ```
+*/
+pub fn inspect(path: &Path) -> io::Result<FileMetadata> {
+    inspect_path(path, true)
+}
+
+/**
+   Get all the metadata of a file at once, without following symbolic links.
+   If the path is a symbolic link, the metadata of the link itself is returned.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<[`FileMetadata`]> -> The metadata of the file.
+*/
+pub fn inspect_nofollow(path: &Path) -> io::Result<FileMetadata> {
+    inspect_path(path, false)
+}
+
+#[cfg(target_os = "linux")]
+fn inspect_path(path: &Path, follow: bool) -> io::Result<FileMetadata> {
+    use crate::metadata::time::statx;
+
+    let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+    let stx = statx(path, flags, libc::STATX_BASIC_STATS | libc::STATX_BTIME)?;
+    let time = |t: libc::statx_timestamp| timestamp_to_local_filetime(t.tv_sec, t.tv_nsec);
+
+    let mode = stx.stx_mode as u32;
+    let kind = FileKind::from_mode(mode);
+    Ok(FileMetadata {
+        kind,
+        size: stx.stx_size,
+        blocks: stx.stx_blocks,
+        mode: Mode::from_bits_truncate(mode),
+        attributes: attributes(path, mode, kind),
+        uid: stx.stx_uid,
+        gid: stx.stx_gid,
+        inode: stx.stx_ino,
+        device: (stx.stx_dev_major, stx.stx_dev_minor),
+        raw_device: match kind {
+            FileKind::BlockDevice | FileKind::CharDevice => Some((stx.stx_rdev_major, stx.stx_rdev_minor)),
+            _ => None,
+        },
+        links: stx.stx_nlink as u64,
+        accessed: time(stx.stx_atime)?,
+        modified: time(stx.stx_mtime)?,
+        changed: time(stx.stx_ctime)?,
+        created: if stx.stx_mask & libc::STATX_BTIME != 0 { Some(time(stx.stx_btime)?) } else { None },
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn inspect_path(path: &Path, follow: bool) -> io::Result<FileMetadata> {
+    use std::os::unix::fs::MetadataExt;
+
+    let meta = if follow { std::fs::metadata(path)? } else { std::fs::symlink_metadata(path)? };
+    let mode = meta.mode();
+    let kind = FileKind::from_mode(mode);
+    let device = |dev: u64| unsafe { (libc::major(dev as libc::dev_t) as u32, libc::minor(dev as libc::dev_t) as u32) };
+    let created = meta.created().ok()
+        .and_then(|c| c.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|c| timestamp_to_local_filetime(c.as_secs() as i64, c.subsec_nanos()))
+        .transpose()?;
+
+    Ok(FileMetadata {
+        kind,
+        size: meta.size(),
+        blocks: meta.blocks(),
+        mode: Mode::from_bits_truncate(mode),
+        attributes: attributes(path, mode, kind),
+        uid: meta.uid(),
+        gid: meta.gid(),
+        inode: meta.ino(),
+        device: device(meta.dev()),
+        raw_device: match kind {
+            FileKind::BlockDevice | FileKind::CharDevice => Some(device(meta.rdev())),
+            _ => None,
+        },
+        links: meta.nlink(),
+        accessed: timestamp_to_local_filetime(meta.atime(), meta.atime_nsec() as u32)?,
+        modified: timestamp_to_local_filetime(meta.mtime(), meta.mtime_nsec() as u32)?,
+        changed: timestamp_to_local_filetime(meta.ctime(), meta.ctime_nsec() as u32)?,
+        created,
+    })
+}
+
+/// The attributes of a file, the same as `get_attributes` returns.
+fn attributes(path: &Path, mode: u32, kind: FileKind) -> Attributes {
+    let mut attributes = Attributes::empty();
+    if mode & 0o222 == 0 {
+        attributes |= Attributes::READ_ONLY;
+    }
+    if matches!(path.file_name().and_then(|n| n.to_str()), Some(n) if n.starts_with('.')) {
+        attributes |= Attributes::HIDDEN;
+    }
+    // Symbolic links cannot have user xattrs, and reading them needs read access to the file,
+    // so the DOS attributes are left out instead of failing.
+    if kind != FileKind::Symlink {
+        attributes |= Attributes::from_bits_truncate(read_dos_attributes(path).unwrap_or(0));
+    }
+    attributes
+}
@@ -9,7 +9,85 @@ pub mod time;
 */
 #[cfg(feature="metadata")]
 pub mod attribute;
+/**
+    Allows reading and writing extended attributes (xattrs) of files.
+    (Linux and Mac only.)
+*/
+#[cfg(all(feature="metadata", any(target_os = "linux", target_os = "macos")))]
+pub mod xattr;
+/**
+    Allows reading and writing the freedesktop.org common extended attributes,
+    such as tags, comments and the URL a file was downloaded from.
+    (Linux and Mac only.)
+*/
+#[cfg(all(feature="metadata", any(target_os = "linux", target_os = "macos")))]
+pub mod xdg;
+/**
+    Allows reading and changing the POSIX permissions of files, like `chmod`.
+    (Unix only.)
+*/
+#[cfg(all(feature="metadata", unix))]
+pub mod permissions;
+/**
+    Allows reading and changing the user and group that own files, like `chown`.
+    (Unix only.)
+*/
+#[cfg(all(feature="metadata", unix))]
+pub mod owner;
+/**
+    Allows reading and writing POSIX access control lists, like `getfacl` and `setfacl`.
+    (Linux only.)
+*/
+#[cfg(all(feature="metadata", target_os = "linux"))]
+pub mod acl;
+/**
+    Allows reading and setting the capabilities of executable files, like `getcap` and `setcap`.
+    (Linux only.)
+*/
+#[cfg(all(feature="metadata", target_os = "linux"))]
+pub mod file_capabilities;
+/**
+    Allows checking which metadata operations are supported by a filesystem.
+*/
+#[cfg(any(feature="metadata", test))]
+mod capabilities;
+#[cfg(any(feature="metadata", test))]
+pub use self::capabilities::{capabilities, MetadataCapabilities};
+/**
+    Allows reading all the metadata of a file at once.
+    (Unix only.)
+*/
+#[cfg(all(any(feature="metadata", test), unix))]
+mod inspect;
+#[cfg(all(any(feature="metadata", test), unix))]
+pub use self::inspect::{inspect, inspect_nofollow, FileKind, FileMetadata};
+/**
+    Allows copying the metadata of one file to another.
+*/
+#[cfg(any(feature="metadata", test))]
+mod copy;
+#[cfg(any(feature="metadata", test))]
+pub use self::copy::{copy_metadata, CopyOptions, MetadataItem};
+/**
+    Allows reading information about the filesystem a path is on, such as its free space.
+*/
+#[cfg(any(feature="metadata", test))]
+mod filesystem;
+#[cfg(any(feature="metadata", test))]
+pub use self::filesystem::{filesystem_of, FilesystemInfo};
 #[cfg(test)]
 pub mod time;
 #[cfg(test)]
-pub mod attribute;
\ No newline at end of file
+pub mod attribute;
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+pub mod xattr;
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+pub mod xdg;
+#[cfg(all(test, unix))]
+pub mod permissions;
+#[cfg(all(test, unix))]
+pub mod owner;
+#[cfg(all(test, target_os = "linux"))]
+pub mod acl;
+#[cfg(all(test, target_os = "linux"))]
+pub mod file_capabilities;
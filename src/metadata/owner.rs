@@ -0,0 +1,289 @@
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/**
+    A user on the system.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct User {
+    /// The id of the user. (uid)
+    pub id: u32,
+    /// The name of the user, or None if the id is not in the user database.
+    pub name: Option<String>,
+}
+
+/**
+    A group on the system.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Group {
+    /// The id of the group. (gid)
+    pub id: u32,
+    /// The name of the group, or None if the id is not in the group database.
+    pub name: Option<String>,
+}
+
+/**
+    A user or group given by name or by id.
+
+    A name that is not in the database but is a number is used as an id, the same as `chown`.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::owner::Owner;
+
+    let by_name = Owner::from("www-data");
+    let by_id = Owner::from(33);
+    ```
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Owner {
+    /// The name of the user or group.
+    Name(String),
+    /// The id of the user or group.
+    Id(u32),
+}
+
+impl From<&str> for Owner {
+    fn from(name: &str) -> Self {
+        Owner::Name(name.to_string())
+    }
+}
+
+impl From<String> for Owner {
+    fn from(name: String) -> Self {
+        Owner::Name(name)
+    }
+}
+
+impl From<u32> for Owner {
+    fn from(id: u32) -> Self {
+        Owner::Id(id)
+    }
+}
+
+/**
+   Get the user and group that own a file.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<([`User`], [`Group`])> -> The owner and group of the file.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::owner::owner_of;
+   use std::path::Path;
+
+   let (user, group) = owner_of(Path::new("/")).unwrap();
+   assert_eq!(user.id, 0);
+   ```
+*/
+pub fn owner_of(path: &Path) -> io::Result<(User, Group)> {
+    let meta = fs::metadata(path)?;
+    Ok((user_by_id(meta.uid())?, group_by_id(meta.gid())?))
+}
+
+/**
+   Get the user and group that own a file without following symbolic links.
+   If the path is a symbolic link, the owner of the link itself is returned.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<([`User`], [`Group`])> -> The owner and group of the file.
+*/
+pub fn owner_of_nofollow(path: &Path) -> io::Result<(User, Group)> {
+    let meta = fs::symlink_metadata(path)?;
+    Ok((user_by_id(meta.uid())?, group_by_id(meta.gid())?))
+}
+
+/**
+   Change the user and/or group that own a file, like `chown`.
+
+   Changing the owner usually requires root. The owner can change the group to any
+   group they are a member of.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   user: Option<[`Owner`]> -> The new owner, or None to keep the current owner. <br>
+   group: Option<[`Owner`]> -> The new group, or None to keep the current group.
+
+   ## Returns
+   io::Result<()> -> An error of kind `NotFound` if a name does not exist, or an error if the owner could not be changed.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::owner::set_owner;
+   use std::path::Path;
+
+   set_owner(Path::new("/srv/www"), Some("www-data".into()), Some(33.into()));
+   ```
+*/
+pub fn set_owner(path: &Path, user: Option<Owner>, group: Option<Owner>) -> io::Result<()> {
+    change_owner(path, user, group, true)
+}
+
+/**
+   Change the user and/or group that own a file without following symbolic links, like `chown -h`.
+   If the path is a symbolic link, the owner of the link itself is changed.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   user: Option<[`Owner`]> -> The new owner, or None to keep the current owner. <br>
+   group: Option<[`Owner`]> -> The new group, or None to keep the current group.
+
+   ## Returns
+   io::Result<()> -> An error of kind `NotFound` if a name does not exist, or an error if the owner could not be changed.
+*/
+pub fn set_owner_nofollow(path: &Path, user: Option<Owner>, group: Option<Owner>) -> io::Result<()> {
+    change_owner(path, user, group, false)
+}
+
+fn change_owner(path: &Path, user: Option<Owner>, group: Option<Owner>, follow: bool) -> io::Result<()> {
+    // -1 keeps the current value.
+    let uid = match user {
+        Some(user) => resolve_user(&user)?,
+        None => libc::uid_t::MAX,
+    };
+    let gid = match group {
+        Some(group) => resolve_group(&group)?,
+        None => libc::gid_t::MAX,
+    };
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "The path contains a null byte"))?;
+    let result = unsafe {
+        if follow { libc::chown(c_path.as_ptr(), uid, gid) } else { libc::lchown(c_path.as_ptr(), uid, gid) }
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/**
+   Get the id of a user.
+
+   ## Params
+   user: &[`Owner`] -> The name or id of the user.
+
+   ## Returns
+   io::Result<u32> -> The id, or an error of kind `NotFound` if the name does not exist.
+*/
+pub fn resolve_user(user: &Owner) -> io::Result<u32> {
+    match user {
+        Owner::Id(id) => Ok(*id),
+        Owner::Name(name) => match user_by_name(name)? {
+            Some(id) => Ok(id),
+            None => name.parse().map_err(|_| not_found(format!("Unknown user: {}", name))),
+        },
+    }
+}
+
+/**
+   Get the id of a group.
+
+   ## Params
+   group: &[`Owner`] -> The name or id of the group.
+
+   ## Returns
+   io::Result<u32> -> The id, or an error of kind `NotFound` if the name does not exist.
+*/
+pub fn resolve_group(group: &Owner) -> io::Result<u32> {
+    match group {
+        Owner::Id(id) => Ok(*id),
+        Owner::Name(name) => match group_by_name(name)? {
+            Some(id) => Ok(id),
+            None => name.parse().map_err(|_| not_found(format!("Unknown group: {}", name))),
+        },
+    }
+}
+
+fn not_found(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, message)
+}
+
+/// Call a reentrant user database function, growing the buffer until the entry fits.
+fn lookup<F: FnMut(&mut [libc::c_char]) -> libc::c_int>(mut call: F) -> io::Result<()> {
+    let mut buf = vec![0 as libc::c_char; 1024];
+    loop {
+        match call(&mut buf) {
+            0 => return Ok(()),
+            libc::ERANGE if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            // Some systems report a missing entry as an error instead of a null result.
+            libc::ENOENT | libc::ESRCH | libc::EBADF | libc::EPERM => return Ok(()),
+            error => return Err(io::Error::from_raw_os_error(error)),
+        }
+    }
+}
+
+fn user_by_id(id: u32) -> io::Result<User> {
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let mut name = None;
+    lookup(|buf| unsafe {
+        let code = libc::getpwuid_r(id, &mut entry, buf.as_mut_ptr(), buf.len(), &mut result);
+        if code == 0 && !result.is_null() {
+            name = Some(CStr::from_ptr(entry.pw_name).to_string_lossy().into_owned());
+        }
+        code
+    })?;
+    Ok(User { id, name })
+}
+
+fn group_by_id(id: u32) -> io::Result<Group> {
+    let mut entry: libc::group = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::group = std::ptr::null_mut();
+    let mut name = None;
+    lookup(|buf| unsafe {
+        let code = libc::getgrgid_r(id, &mut entry, buf.as_mut_ptr(), buf.len(), &mut result);
+        if code == 0 && !result.is_null() {
+            name = Some(CStr::from_ptr(entry.gr_name).to_string_lossy().into_owned());
+        }
+        code
+    })?;
+    Ok(Group { id, name })
+}
+
+fn user_by_name(name: &str) -> io::Result<Option<u32>> {
+    let c_name = match CString::new(name) {
+        Ok(c_name) => c_name,
+        Err(_) => return Ok(None),
+    };
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let mut id = None;
+    lookup(|buf| unsafe {
+        let code = libc::getpwnam_r(c_name.as_ptr(), &mut entry, buf.as_mut_ptr(), buf.len(), &mut result);
+        if code == 0 && !result.is_null() {
+            id = Some(entry.pw_uid);
+        }
+        code
+    })?;
+    Ok(id)
+}
+
+fn group_by_name(name: &str) -> io::Result<Option<u32>> {
+    let c_name = match CString::new(name) {
+        Ok(c_name) => c_name,
+        Err(_) => return Ok(None),
+    };
+    let mut entry: libc::group = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::group = std::ptr::null_mut();
+    let mut id = None;
+    lookup(|buf| unsafe {
+        let code = libc::getgrnam_r(c_name.as_ptr(), &mut entry, buf.as_mut_ptr(), buf.len(), &mut result);
+        if code == 0 && !result.is_null() {
+            id = Some(entry.gr_gid);
+        }
+        code
+    })?;
+    Ok(id)
+}
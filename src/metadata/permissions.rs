@@ -0,0 +1,419 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::str::FromStr;
+
+bitflags! {
+    /**
+        The POSIX permission bits of a file.
+
+        # Examples
+        ```rust
+        use system_extensions::metadata::permissions::Mode;
+
+        let mode = Mode::from_octal("755").unwrap();
+        assert!(mode.contains(Mode::USER_WRITE | Mode::OTHER_EXECUTE));
+        assert_eq!(mode.to_string(), "rwxr-xr-x");
+        assert_eq!(mode.to_octal(), "0755");
+        ```
+    */
+    pub struct Mode: u32 {
+        /// The owner can read the file.
+        const USER_READ = 0o400;
+        /// The owner can write to the file.
+        const USER_WRITE = 0o200;
+        /// The owner can execute the file, or search the directory.
+        const USER_EXECUTE = 0o100;
+        /// Members of the group can read the file.
+        const GROUP_READ = 0o040;
+        /// Members of the group can write to the file.
+        const GROUP_WRITE = 0o020;
+        /// Members of the group can execute the file, or search the directory.
+        const GROUP_EXECUTE = 0o010;
+        /// Everyone else can read the file.
+        const OTHER_READ = 0o004;
+        /// Everyone else can write to the file.
+        const OTHER_WRITE = 0o002;
+        /// Everyone else can execute the file, or search the directory.
+        const OTHER_EXECUTE = 0o001;
+        /// The file is executed as its owner.
+        const SETUID = 0o4000;
+        /// The file is executed as its group, or new files in the directory get its group.
+        const SETGID = 0o2000;
+        /// Only the owner of a file in the directory can delete or rename it.
+        const STICKY = 0o1000;
+
+        /// All permissions for the owner.
+        const USER_ALL = 0o700;
+        /// All permissions for the group.
+        const GROUP_ALL = 0o070;
+        /// All permissions for everyone else.
+        const OTHER_ALL = 0o007;
+    }
+}
+
+impl Mode {
+    /**
+        Parse an octal mode, like `chmod 755`.
+
+        # Params
+        octal: &str -> One to four octal digits, not counting leading zeros. (ex: `755` or `00755`)<br>
+
+        # Returns
+        io::Result<Mode> -> The mode, or an error if the string is not a valid octal mode.
+    */
+    pub fn from_octal(octal: &str) -> io::Result<Mode> {
+        // Like chmod, any number of leading zeros is allowed.
+        let significant = octal.trim_start_matches('0');
+        if octal.is_empty() || significant.len() > 4 || !octal.chars().all(|c| ('0'..='7').contains(&c)) {
+            return Err(invalid_input(format!("Invalid octal mode: {}", octal)));
+        }
+        Ok(Mode::from_bits_truncate(u32::from_str_radix(octal, 8).unwrap()))
+    }
+
+    /**
+        Get the mode as four octal digits. (ex: `0755`)
+    */
+    pub fn to_octal(self) -> String {
+        format!("{:04o}", self.bits)
+    }
+}
+
+impl fmt::Display for Mode {
+    /**
+        Formats the mode the same as `ls -l`, without the file type. (ex: `rwxr-sr-t`)
+    */
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let classes = [(6, Mode::SETUID, 's'), (3, Mode::SETGID, 's'), (0, Mode::STICKY, 't')];
+        for (shift, special, letter) in classes.iter() {
+            let bits = (self.bits >> shift) & 0o7;
+            let execute = match (bits & 0o1 != 0, self.contains(*special)) {
+                (true, false) => 'x',
+                (false, false) => '-',
+                (true, true) => *letter,
+                (false, true) => letter.to_ascii_uppercase(),
+            };
+            write!(f, "{}{}{}",
+                   if bits & 0o4 != 0 { 'r' } else { '-' },
+                   if bits & 0o2 != 0 { 'w' } else { '-' },
+                   execute)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+    A change to a mode, parsed from a `chmod` style expression.
+
+    Both octal modes (`644`) and symbolic expressions (`u+x,g-w,o=r`) are supported.
+    A symbolic expression is a list of clauses separated by `,`. Each clause has:
+    - who: any of `u` (owner), `g` (group), `o` (others) and `a` (all). Nothing is the same as `a`,
+      but unlike `chmod` the umask is not applied.
+    - one or more operations: `+` (add), `-` (remove) or `=` (set), followed by any of `r`, `w`, `x`,
+      `X` (execute if it is a directory or already executable by someone), `s` (setuid/setgid) and `t` (sticky),
+      or by one of `u`, `g` or `o` to copy the permissions of that class.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::permissions::{Mode, ModeChange};
+
+    let change: ModeChange = "u+x,g-w,o=r".parse().unwrap();
+    let mode = change.apply(Mode::from_octal("664").unwrap(), false);
+    assert_eq!(mode.to_octal(), "0744");
+    ```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeChange {
+    clauses: Vec<Clause>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Clause {
+    Absolute(Mode),
+    Symbolic { who: u32, operations: Vec<Operation> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Operation {
+    op: char,
+    perms: Perms,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Perms {
+    /// The letters given, as `chmod` letters.
+    Letters(String),
+    /// Copy the permissions of the class with this shift.
+    Copy(u32),
+}
+
+/// The bits of the user, group and other classes.
+const WHO_USER: u32 = 0o4700;
+const WHO_GROUP: u32 = 0o2070;
+const WHO_OTHER: u32 = 0o1007;
+const WHO_ALL: u32 = WHO_USER | WHO_GROUP | WHO_OTHER;
+
+impl ModeChange {
+    /**
+        Parse a `chmod` style expression.
+
+        # Params
+        expression: &str -> An octal mode or a symbolic expression.<br>
+
+        # Returns
+        io::Result<ModeChange> -> The change, or an error if the expression is invalid.
+    */
+    pub fn parse(expression: &str) -> io::Result<ModeChange> {
+        if expression.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(ModeChange { clauses: vec![Clause::Absolute(Mode::from_octal(expression)?)] });
+        }
+        let clauses = expression.split(',')
+            .map(|clause| parse_clause(clause).ok_or_else(|| invalid_input(format!("Invalid mode: {}", expression))))
+            .collect::<io::Result<Vec<Clause>>>()?;
+        Ok(ModeChange { clauses })
+    }
+
+    /**
+        Apply the change to a mode.
+
+        # Params
+        mode: [`Mode`] -> The current mode.<br>
+        is_dir: bool -> If the mode is of a directory. (Used by `X`.)<br>
+
+        # Returns
+        [`Mode`] -> The new mode.
+    */
+    pub fn apply(&self, mode: Mode, is_dir: bool) -> Mode {
+        let mut bits = mode.bits;
+        for clause in &self.clauses {
+            match clause {
+                Clause::Absolute(absolute) => bits = absolute.bits,
+                Clause::Symbolic { who, operations } => {
+                    for operation in operations {
+                        bits = apply_operation(bits, *who, operation, is_dir);
+                    }
+                }
+            }
+        }
+        Mode::from_bits_truncate(bits)
+    }
+}
+
+impl FromStr for ModeChange {
+    type Err = io::Error;
+
+    /**
+        Parses a `chmod` style expression, see [`ModeChange::parse`].
+    */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ModeChange::parse(s)
+    }
+}
+
+fn parse_clause(clause: &str) -> Option<Clause> {
+    let who_end = clause.find(['+', '-', '='])?;
+    let mut who = 0;
+    for c in clause[..who_end].chars() {
+        who |= match c {
+            'u' => WHO_USER,
+            'g' => WHO_GROUP,
+            'o' => WHO_OTHER,
+            'a' => WHO_ALL,
+            _ => return None,
+        };
+    }
+
+    let mut operations: Vec<Operation> = Vec::new();
+    for c in clause[who_end..].chars() {
+        match c {
+            '+' | '-' | '=' => operations.push(Operation { op: c, perms: Perms::Letters(String::new()) }),
+            'r' | 'w' | 'x' | 'X' | 's' | 't' => match &mut operations.last_mut()?.perms {
+                Perms::Letters(letters) => letters.push(c),
+                Perms::Copy(_) => return None,
+            },
+            'u' | 'g' | 'o' => {
+                let last = operations.last_mut()?;
+                if last.perms != Perms::Letters(String::new()) {
+                    return None;
+                }
+                last.perms = Perms::Copy(match c { 'u' => 6, 'g' => 3, _ => 0 });
+            }
+            _ => return None,
+        }
+    }
+    Some(Clause::Symbolic { who, operations })
+}
+
+fn apply_operation(bits: u32, who: u32, operation: &Operation, is_dir: bool) -> u32 {
+    // Nothing given means all, and `t` only applies then or to others.
+    let (who, sticky_allowed) = if who == 0 { (WHO_ALL, true) } else { (who, who & WHO_OTHER == WHO_OTHER) };
+
+    let change = match &operation.perms {
+        Perms::Copy(shift) => {
+            let class = (bits >> shift) & 0o7;
+            (class << 6 | class << 3 | class) & who & 0o777
+        }
+        Perms::Letters(letters) => {
+            let mut rwx = 0;
+            let mut special = 0;
+            for c in letters.chars() {
+                match c {
+                    'r' => rwx |= 0o4,
+                    'w' => rwx |= 0o2,
+                    'x' => rwx |= 0o1,
+                    'X' if is_dir || bits & 0o111 != 0 => rwx |= 0o1,
+                    's' => special |= 0o6000,
+                    't' if sticky_allowed => special |= 0o1000,
+                    _ => {}
+                }
+            }
+            ((rwx << 6 | rwx << 3 | rwx) | special) & who
+        }
+    };
+
+    match operation.op {
+        '+' => bits | change,
+        '-' => bits & !change,
+        // `=` keeps the special bits of directories, the same as chmod.
+        _ => {
+            let cleared = if is_dir { who & 0o777 } else { who };
+            (bits & !cleared) | change
+        }
+    }
+}
+
+/**
+   Get the permission bits of a file.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<[`Mode`]> -> The mode of the file.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::permissions::get_mode;
+   use std::path::Path;
+
+   if let Ok(mode) = get_mode(Path::new("/test.txt")) {
+       println!("{}", mode);
+   }
+   ```
+*/
+pub fn get_mode(path: &Path) -> io::Result<Mode> {
+    Ok(Mode::from_bits_truncate(fs::metadata(path)?.permissions().mode()))
+}
+
+/**
+   Set the permission bits of a file.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   mode: [`Mode`] -> The new mode.
+
+   ## Returns
+   io::Result<()> -> An error if the mode could not be set.
+*/
+pub fn set_mode(path: &Path, mode: Mode) -> io::Result<()> {
+    fs::set_permissions(path, fs::Permissions::from_mode(mode.bits))
+}
+
+/**
+   Change the permissions of a file relative to its current mode, like `chmod`.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   change: &[`ModeChange`] -> The change to apply.
+
+   ## Returns
+   io::Result<[`Mode`]> -> The new mode of the file.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::permissions::chmod;
+   use std::path::Path;
+
+   chmod(Path::new("/test.sh"), &"u+x,go-w".parse().unwrap());
+   ```
+*/
+pub fn chmod(path: &Path, change: &ModeChange) -> io::Result<Mode> {
+    let meta = fs::metadata(path)?;
+    let old = Mode::from_bits_truncate(meta.permissions().mode());
+    let new = change.apply(old, meta.is_dir());
+    if new != old {
+        set_mode(path, new)?;
+    }
+    Ok(new)
+}
+
+/**
+   Change the permissions of a directory and everything in it, with separate changes
+   for files and directories. (Like `chmod -R`.)
+
+   Symbolic links are not followed or changed.
+
+   ## Params
+   dir: &Path -> The directory to change. <br>
+   files: &[`ModeChange`] -> The change to apply to every file. <br>
+   dirs: &[`ModeChange`] -> The change to apply to every directory, including `dir`.
+
+   ## Returns
+   io::Result<usize> -> The amount of entries whose mode changed.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::permissions::{chmod_recursive, ModeChange};
+   use std::path::Path;
+
+   // Files are rw-r--r--, directories are rwxr-xr-x.
+   chmod_recursive(Path::new("./public"), &"644".parse().unwrap(), &"755".parse().unwrap());
+   ```
+*/
+pub fn chmod_recursive(dir: &Path, files: &ModeChange, dirs: &ModeChange) -> io::Result<usize> {
+    let mut changed = 0;
+    chmod_entry(dir, files, dirs, &mut changed)?;
+    Ok(changed)
+}
+
+fn chmod_entry(path: &Path, files: &ModeChange, dirs: &ModeChange, changed: &mut usize) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.file_type().is_symlink() {
+        return Ok(());
+    }
+    let old = Mode::from_bits_truncate(meta.permissions().mode());
+    if !meta.is_dir() {
+        let new = files.apply(old, false);
+        if new != old {
+            set_mode(path, new)?;
+            *changed += 1;
+        }
+        return Ok(());
+    }
+
+    // The directory has to be readable and searchable to walk it, so permissions
+    // are added before walking and taken away after.
+    let new = dirs.apply(old, true);
+    let before = new.contains(Mode::USER_READ | Mode::USER_EXECUTE);
+    if before && new != old {
+        set_mode(path, new)?;
+    }
+    let mut entries = fs::read_dir(path)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        chmod_entry(&entry.path(), files, dirs, changed)?;
+    }
+    if new != old {
+        if !before {
+            set_mode(path, new)?;
+        }
+        *changed += 1;
+    }
+    Ok(())
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
@@ -1,485 +1,1958 @@
 #[cfg(windows)]
 extern crate winapi;
 
-use std::ffi::OsStr;
-use std::iter::once;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io;
 use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(target_os = "macos")]
 use std::process::Command;
 
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, Offset,
+             SecondsFormat, TimeZone, Timelike, Utc};
+
 
 /**
-    Manages the imports for machines running on windows.
+    Which dates of a file to change.
 */
-#[cfg(windows)]
-macro_rules! windows_imports {
-    () => {
-        use winapi::um::winnt;
-        use self::winapi::um::handleapi::CloseHandle;
-        use winapi::um::sysinfoapi;
-        use self::winapi::um::fileapi::{OPEN_EXISTING};
-        use self::winapi::shared::minwindef::{FILETIME};
-        use self::winapi::_core::ptr::{null_mut};
-        use self::winapi::um::winnt::{FILE_WRITE_ATTRIBUTES, FILE_ATTRIBUTE_NORMAL, FILE_SHARE_READ, FILE_SHARE_WRITE, GENERIC_WRITE};
-        use self::winapi::um::minwinbase::SYSTEMTIME;
-        use self::winapi::um::timezoneapi::SystemTimeToFileTime;
-        use winapi::um::fileapi::SetFileTime;
-        use std::os::windows::ffi::OsStrExt;
-    };
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Which {
+    /// Only the accessed date.
+    Accessed,
+    /// Only the modified date.
+    Modified,
+    /// Both the accessed and modified dates.
+    Both,
+}
+
+/**
+    The timezone the fields of a [`FileTime`] are expressed in.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Zone {
+    /// The local timezone of the system, including daylight saving time.
+    #[default]
+    Local,
+    /// Coordinated Universal Time.
+    Utc,
+    /// A fixed offset from UTC.
+    Offset(FixedOffset),
 }
 
 /**
     Represents the time for a file.
+
+    Every field is optional. Fields that are not specified are filled in using the current
+    time when the file time is applied. A FileTime is always valid, it can only be created
+    through [`FileTime::new`], the [`FileTimeBuilder`], parsing, or a conversion.
+
+    The fields are expressed in a [`Zone`], which is the local timezone unless specified
+    otherwise. Every platform converts the fields the same way.
+    Local times that are ambiguous or skipped due to daylight saving time are reported
+    as errors when the file time is applied.
+    Dates read from files are in local time with their UTC offset (a [`Zone::Offset`]),
+    so they can always be applied again.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::time::FileTime;
+
+    let christmas = FileTime::new(25, 12, 2021).unwrap();
+    let evening = FileTime::builder()
+        .set_year(2021)
+        .set_month(12)
+        .set_day(24)
+        .set_hour(18)
+        .set_minute(30)
+        .build()
+        .unwrap();
+    let parsed: FileTime = "2021-12-24T18:30:00.5".parse().unwrap();
+    println!("{}", parsed);
+    ```
 */
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FileTime {
-    pub day: i16,
-    pub month: i16,
-    pub year: i16,
-    pub hour: i16,
-    pub minute: i16,
-    pub second: i16,
-    pub milliseconds: i16,
+    year: Option<i32>,
+    month: Option<u32>,
+    day: Option<u32>,
+    hour: Option<u32>,
+    minute: Option<u32>,
+    second: Option<u32>,
+    nanosecond: Option<u32>,
+    zone: Zone,
 }
 
 impl FileTime {
     /**
-        Create a new file time.
+        Create a new file time for a date.
+
+        ## Params
+        day: u32 -> The day of the month. (1-31) <br>
+        month: u32 -> The month. (1-12) <br>
+        year: i32 -> The year.
+
+        ## Returns
+        io::Result<FileTime> -> An `InvalidInput` error if the date does not exist.
+    */
+    pub fn new(day: u32, month: u32, year: i32) -> io::Result<FileTime> {
+        FileTime::builder().set_day(day).set_month(month).set_year(year).build()
+    }
+
+    /**
+        Create a builder to specify a file time field by field.
+    */
+    pub fn builder() -> FileTimeBuilder {
+        FileTimeBuilder::default()
+    }
+
+    /**
+        The current time, with every field specified.
+    */
+    pub fn now() -> FileTime {
+        FileTime::from(Local::now().naive_local())
+    }
+
+    /// The year, if specified.
+    pub fn year(&self) -> Option<i32> {
+        self.year
+    }
+
+    /// The month (1-12), if specified.
+    pub fn month(&self) -> Option<u32> {
+        self.month
+    }
+
+    /// The day of the month (1-31), if specified.
+    pub fn day(&self) -> Option<u32> {
+        self.day
+    }
+
+    /// The hour (0-23), if specified.
+    pub fn hour(&self) -> Option<u32> {
+        self.hour
+    }
+
+    /// The minute (0-59), if specified.
+    pub fn minute(&self) -> Option<u32> {
+        self.minute
+    }
+
+    /// The second (0-59), if specified.
+    pub fn second(&self) -> Option<u32> {
+        self.second
+    }
+
+    /// The nanoseconds within the second, if specified.
+    pub fn nanosecond(&self) -> Option<u32> {
+        self.nanosecond
+    }
+
+    /// The timezone the fields are expressed in.
+    pub fn zone(&self) -> Zone {
+        self.zone
+    }
+
+    /**
+        Parse an ISO-8601 date or date and time.
+
+        Accepted forms are `2021-12-25`, `2021-12-25T18:30`, `2021-12-25T18:30:05`,
+        `2021-12-25T18:30:05.123456789` (a space may be used instead of the `T`), and any of
+        these followed by a `Z` or an offset such as `+02:00`.
+        A date without a time leaves the time unspecified.
+        Times without an offset are in the local timezone, times ending in `Z` are in UTC.
+
+        ## Params
+        text: &str -> The text to parse.
+
+        ## Returns
+        io::Result<FileTime> -> An `InvalidInput` error if the text could not be parsed.
+    */
+    pub fn parse_iso8601(text: &str) -> io::Result<FileTime> {
+        let text = text.trim();
+        if let Ok(time) = FileTime::parse_rfc3339(text) {
+            return Ok(time);
+        }
+        for format in ["%Y-%m-%dT%H:%M%#z", "%Y-%m-%d %H:%M%#z"].iter() {
+            if let Ok(date_time) = DateTime::parse_from_str(text, format) {
+                return Ok(FileTime::from(date_time).utc_if_zulu(text));
+            }
+        }
+        for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"].iter() {
+            if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+                return Ok(FileTime::from(naive));
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+            return FileTime::new(date.day(), date.month(), date.year());
+        }
+        Err(invalid_input(format!("Unable to parse '{}' as an ISO-8601 date.", text)))
+    }
+
+    /**
+        Parse an RFC 3339 timestamp, such as `2021-12-25T18:30:05.5+02:00`.
+
+        ## Params
+        text: &str -> The text to parse.
+
+        ## Returns
+        io::Result<FileTime> -> An `InvalidInput` error if the text could not be parsed.
+    */
+    pub fn parse_rfc3339(text: &str) -> io::Result<FileTime> {
+        DateTime::parse_from_rfc3339(text.trim())
+            .map(|date_time| FileTime::from(date_time).utc_if_zulu(text))
+            .map_err(|e| invalid_input(format!("Unable to parse '{}' as an RFC 3339 timestamp: {}", text, e)))
+    }
+
+    /**
+        Format the file time as ISO-8601 in its own zone, such as `2021-12-25T18:30:05.5`.
+        UTC times end with `Z` and fixed offsets end with the offset.
+        Unspecified fields are filled in using the current time.
+    */
+    pub fn to_iso8601(&self) -> String {
+        let naive = self.resolve().format("%Y-%m-%dT%H:%M:%S%.f");
+        match self.zone {
+            Zone::Local => naive.to_string(),
+            Zone::Utc => format!("{}Z", naive),
+            Zone::Offset(offset) => format!("{}{}", naive, offset),
+        }
+    }
+
+    /**
+        Format the file time as an RFC 3339 timestamp, such as `2021-12-25T18:30:05.5+01:00`.
+        Unspecified fields are filled in using the current time.
+    */
+    pub fn to_rfc3339(&self) -> String {
+        self.to_datetime_lenient().to_rfc3339_opts(SecondsFormat::AutoSi, self.zone == Zone::Utc)
+    }
+
+    /**
+        Format the file time the way `touch -t` expects it, such as `202112251830.05`.
+        `touch` uses the local timezone, so the time is converted into it.
+        Unspecified fields are filled in using the current time.
+    */
+    pub fn to_touch_format(&self) -> String {
+        self.to_datetime_lenient().with_timezone(&Local).format("%Y%m%d%H%M.%S").to_string()
+    }
+
+    /**
+        Convert the file time into a [`SystemTime`].
+
+        ## Returns
+        io::Result<SystemTime> -> An `InvalidInput` error if the time is in the local timezone
+        and is ambiguous (it happens twice when the clocks go back) or does not exist (it is
+        skipped when the clocks go forward).
+    */
+    pub fn to_system_time(&self) -> io::Result<SystemTime> {
+        self.to_datetime().map(datetime_to_system_time)
+    }
+
+    /**
+        Fill in the unspecified fields using the current time in the zone of the file time.
+
+        If filling in the fields creates a date that does not exist (such as the 31st of a
+        month with 30 days) the day is clamped to the last day of the month.
+    */
+    pub(crate) fn resolve(&self) -> NaiveDateTime {
+        let now = match self.zone {
+            Zone::Local => Local::now().naive_local(),
+            Zone::Utc => Utc::now().naive_utc(),
+            Zone::Offset(offset) => Utc::now().with_timezone(&offset).naive_local(),
+        };
+        let year = self.year.unwrap_or_else(|| now.year());
+        let month = self.month.unwrap_or_else(|| now.month());
+        let day = self.day.unwrap_or_else(|| now.day()).min(days_in_month(year, month));
+
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_nano_opt(self.hour.unwrap_or_else(|| now.hour()),
+                                                   self.minute.unwrap_or_else(|| now.minute()),
+                                                   self.second.unwrap_or_else(|| now.second()),
+                                                   self.nanosecond.unwrap_or(0)))
+            .expect("A FileTime is always valid")
+    }
+
+    /**
+        Resolve the file time into an exact point in time.
+        Ambiguous and skipped local times are reported as an error.
+    */
+    pub(crate) fn to_datetime(self) -> io::Result<DateTime<FixedOffset>> {
+        let naive = self.resolve();
+        match self.zone {
+            Zone::Utc => Ok(Utc.from_utc_datetime(&naive).fixed_offset()),
+            Zone::Offset(offset) => offset.from_local_datetime(&naive).single()
+                .ok_or_else(|| invalid_input(format!("The time {} is out of range.", naive))),
+            Zone::Local => match Local.from_local_datetime(&naive) {
+                LocalResult::Single(local) => Ok(local.fixed_offset()),
+                LocalResult::Ambiguous(first, second) => Err(invalid_input(format!(
+                    "The local time {} is ambiguous, it could be {} or {}.", naive, first.to_rfc3339(), second.to_rfc3339()))),
+                LocalResult::None => Err(invalid_input(format!(
+                    "The local time {} does not exist due to a daylight saving time transition.", naive))),
+            }
+        }
+    }
+
+    /**
+        Resolve the file time into an exact point in time.
+
+        An ambiguous local time resolves to the earliest of the two times. A local time that
+        was skipped is moved forward by the length of the gap.
+    */
+    pub(crate) fn to_datetime_lenient(self) -> DateTime<FixedOffset> {
+        self.to_datetime().unwrap_or_else(|_| {
+            let naive = self.resolve();
+            let earliest = |naive: &NaiveDateTime| match Local.from_local_datetime(naive) {
+                LocalResult::Single(local) => Some(local),
+                LocalResult::Ambiguous(first, second) => Some(first.min(second)),
+                LocalResult::None => None,
+            };
+            earliest(&naive)
+                .or_else(|| earliest(&(naive + Duration::hours(1))))
+                .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+                .fixed_offset()
+        })
+    }
+
+    /**
+        Mark a parsed time as UTC if the text used the `Z` suffix.
+    */
+    fn utc_if_zulu(mut self, text: &str) -> FileTime {
+        if text.trim().ends_with(['Z', 'z']) {
+            self.zone = Zone::Utc;
+        }
+        self
+    }
+}
+
+impl Default for FileTime {
+    /**
+        A file time with no fields specified. (Which is the current time once applied.)
     */
-    pub fn new(day: i16, month: i16, year: i16) -> FileTime {
+    fn default() -> Self {
         FileTime {
-            day,
-            month,
-            year,
-            hour: -1,
-            minute: -1,
-            second: -1,
-            milliseconds: -1,
+            year: None,
+            month: None,
+            day: None,
+            hour: None,
+            minute: None,
+            second: None,
+            nanosecond: None,
+            zone: Zone::Local,
         }
     }
 }
 
-#[cfg(windows)]
-unsafe fn filetime_to_systime(system_time: *mut winapi::um::minwinbase::SYSTEMTIME, time: &FileTime) {
-    if time.day != -1 {
-        (*system_time).wDay = time.day as u16;
+impl fmt::Display for FileTime {
+    /**
+        Displays the specified fields in ISO-8601 order, with `-` for unspecified fields.
+        The time is only shown if any of its fields are specified.
+        UTC times end with `Z` and fixed offsets end with the offset.
+    */
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn field(value: Option<u32>, width: usize) -> String {
+            value.map_or_else(|| "-".repeat(width), |v| format!("{:0width$}", v, width = width))
+        }
+        let year = self.year.map_or_else(|| "----".to_string(), |y| format!("{:04}", y));
+        write!(f, "{}-{}-{}", year, field(self.month, 2), field(self.day, 2))?;
+        if self.hour.is_some() || self.minute.is_some() || self.second.is_some() || self.nanosecond.is_some() {
+            write!(f, "T{}:{}:{}", field(self.hour, 2), field(self.minute, 2), field(self.second, 2))?;
+            if let Some(nanos) = self.nanosecond.filter(|n| *n != 0) {
+                write!(f, ".{}", format!("{:09}", nanos).trim_end_matches('0'))?;
+            }
+        }
+        match self.zone {
+            Zone::Local => Ok(()),
+            Zone::Utc => write!(f, "Z"),
+            Zone::Offset(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+impl FromStr for FileTime {
+    type Err = io::Error;
+
+    /**
+        Parses an ISO-8601 date, see [`FileTime::parse_iso8601`].
+    */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FileTime::parse_iso8601(s)
+    }
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for FileTime {
+    /**
+        Keeps the fields and the offset of the date time. (As a [`Zone::Offset`].)
+    */
+    fn from(date_time: DateTime<Tz>) -> Self {
+        let offset = date_time.offset().fix();
+        let mut time = FileTime::from(date_time.naive_local());
+        time.zone = Zone::Offset(offset);
+        time
+    }
+}
+
+impl From<NaiveDateTime> for FileTime {
+    /**
+        Uses the date time as a local time. (In [`Zone::Local`].)
+    */
+    fn from(naive: NaiveDateTime) -> Self {
+        FileTime {
+            year: Some(naive.year()),
+            month: Some(naive.month()),
+            day: Some(naive.day()),
+            hour: Some(naive.hour()),
+            minute: Some(naive.minute()),
+            second: Some(naive.second()),
+            nanosecond: Some(naive.nanosecond().min(999_999_999)),
+            zone: Zone::Local,
+        }
+    }
+}
+
+impl From<FileTime> for DateTime<Local> {
+    /**
+        Ambiguous and skipped local times are resolved leniently, use
+        [`FileTime::to_system_time`] to have them reported.
+    */
+    fn from(time: FileTime) -> Self {
+        time.to_datetime_lenient().with_timezone(&Local)
+    }
+}
+
+impl From<FileTime> for DateTime<FixedOffset> {
+    /**
+        Ambiguous and skipped local times are resolved leniently, use
+        [`FileTime::to_system_time`] to have them reported.
+    */
+    fn from(time: FileTime) -> Self {
+        time.to_datetime_lenient()
+    }
+}
+
+impl From<FileTime> for SystemTime {
+    /**
+        Ambiguous and skipped local times are resolved leniently, use
+        [`FileTime::to_system_time`] to have them reported.
+    */
+    fn from(time: FileTime) -> Self {
+        datetime_to_system_time(time.to_datetime_lenient())
+    }
+}
+
+fn datetime_to_system_time(date_time: DateTime<FixedOffset>) -> SystemTime {
+    let nanos = date_time.timestamp_subsec_nanos();
+    let seconds = date_time.timestamp();
+    if seconds >= 0 {
+        UNIX_EPOCH + std::time::Duration::new(seconds as u64, nanos)
+    } else {
+        UNIX_EPOCH - std::time::Duration::new(seconds.unsigned_abs(), 0) + std::time::Duration::new(0, nanos)
+    }
+}
+
+impl TryFrom<SystemTime> for FileTime {
+    type Error = io::Error;
+
+    /**
+        Converts the system time into local time, keeping the UTC offset. (In a [`Zone::Offset`].)
+        Fails if the system time is out of the supported range.
+    */
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let (seconds, nanos) = match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => (duration.as_secs() as i64, duration.subsec_nanos()),
+            Err(e) => {
+                let before = e.duration();
+                if before.subsec_nanos() == 0 {
+                    (-(before.as_secs() as i64), 0)
+                } else {
+                    (-(before.as_secs() as i64) - 1, 1_000_000_000 - before.subsec_nanos())
+                }
+            }
+        };
+        timestamp_to_local_filetime(seconds, nanos)
+    }
+}
+
+/**
+    A builder to create a [`FileTime`] field by field.
+    Fields that are not set are left unspecified.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct FileTimeBuilder {
+    time: FileTime,
+}
+
+impl FileTimeBuilder {
+    /// Set the year.
+    pub fn set_year(&mut self, year: i32) -> &mut Self {
+        self.time.year = Some(year);
+        self
+    }
+
+    /// Set the month. (1-12)
+    pub fn set_month(&mut self, month: u32) -> &mut Self {
+        self.time.month = Some(month);
+        self
+    }
+
+    /// Set the day of the month. (1-31)
+    pub fn set_day(&mut self, day: u32) -> &mut Self {
+        self.time.day = Some(day);
+        self
+    }
+
+    /// Set the hour. (0-23)
+    pub fn set_hour(&mut self, hour: u32) -> &mut Self {
+        self.time.hour = Some(hour);
+        self
+    }
+
+    /// Set the minute. (0-59)
+    pub fn set_minute(&mut self, minute: u32) -> &mut Self {
+        self.time.minute = Some(minute);
+        self
     }
-    if time.month != -1 {
-        (*system_time).wMonth = time.month as u16;
+
+    /// Set the second. (0-59)
+    pub fn set_second(&mut self, second: u32) -> &mut Self {
+        self.time.second = Some(second);
+        self
     }
-    if time.year != -1 {
-        (*system_time).wYear = time.year as u16;
+
+    /// Set the milliseconds within the second. (0-999)
+    pub fn set_millisecond(&mut self, millisecond: u32) -> &mut Self {
+        self.time.nanosecond = Some(millisecond.saturating_mul(1_000_000));
+        self
     }
-    if time.hour != -1 {
-        (*system_time).wHour = time.hour as u16;
+
+    /// Set the nanoseconds within the second. (0-999,999,999)
+    pub fn set_nanosecond(&mut self, nanosecond: u32) -> &mut Self {
+        self.time.nanosecond = Some(nanosecond);
+        self
     }
-    if time.minute != -1 {
-        (*system_time).wMinute = time.minute as u16;
+
+    /// Set the timezone the fields are expressed in. (Local by default.)
+    pub fn set_zone(&mut self, zone: Zone) -> &mut Self {
+        self.time.zone = zone;
+        self
     }
-    if time.second != -1 {
-        (*system_time).wSecond = time.second as u16;
+
+    /**
+        Validate the fields and create the [`FileTime`].
+
+        ## Returns
+        io::Result<[`FileTime`]> -> An `InvalidInput` error if a field is out of range or the
+        date does not exist. (Such as the 30th of February.)
+    */
+    pub fn build(&self) -> io::Result<FileTime> {
+        let time = self.time;
+        let check = |value: Option<u32>, min: u32, max: u32, name: &str| -> io::Result<()> {
+            match value {
+                Some(v) if v < min || v > max => Err(invalid_input(format!("The {} {} is not between {} and {}.", name, v, min, max))),
+                _ => Ok(())
+            }
+        };
+        check(time.month, 1, 12, "month")?;
+        check(time.day, 1, 31, "day")?;
+        check(time.hour, 0, 23, "hour")?;
+        check(time.minute, 0, 59, "minute")?;
+        check(time.second, 0, 59, "second")?;
+        check(time.nanosecond, 0, 999_999_999, "nanosecond")?;
+        if let Some(year) = time.year {
+            if NaiveDate::from_ymd_opt(year, 1, 1).is_none() {
+                return Err(invalid_input(format!("The year {} is out of range.", year)));
+            }
+        }
+
+        // Without a year, allow the 29th of February since it exists in leap years.
+        if let (Some(month), Some(day)) = (time.month, time.day) {
+            if day > days_in_month(time.year.unwrap_or(2000), month) {
+                return Err(invalid_input(format!("The day {} does not exist in month {}.", day, month)));
+            }
+        }
+        Ok(time)
     }
-    if time.milliseconds != -1 {
-        (*system_time).wMilliseconds = time.milliseconds as u16;
+}
+
+/**
+    The times to set on an open file using [`set_times`].
+    Times that are `None` are left unchanged.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::time::{Times, FileTime};
+
+    let times = Times {
+        modified: Some(FileTime::now()),
+        ..Times::default()
+    };
+    ```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Times {
+    /// The accessed date.
+    pub accessed: Option<FileTime>,
+    /// The modified date.
+    pub modified: Option<FileTime>,
+    /// The creation date. (Only supported on Windows.)
+    pub created: Option<FileTime>,
+}
+
+/**
+    The number of days in a month of a year.
+*/
+fn days_in_month(year: i32, month: u32) -> u32 {
+    (28..=31).rev()
+        .find(|day| NaiveDate::from_ymd_opt(year, month, *day).is_some())
+        .unwrap_or(28)
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/**
+    Convert seconds and nanoseconds since the Unix epoch into a [`FileTime`] in UTC.
+    Unlike local time, this can never be ambiguous when the time is applied again.
+*/
+fn timestamp_to_utc_filetime(seconds: i64, nanos: u32) -> io::Result<FileTime> {
+    let utc = Utc.timestamp_opt(seconds, nanos).single()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "The file time is out of range."))?;
+    let mut time = FileTime::from(utc.naive_utc());
+    time.zone = Zone::Utc;
+    Ok(time)
+}
+
+/**
+    Convert seconds and nanoseconds since the Unix epoch into a [`FileTime`] in local time.
+    The UTC offset is kept (as a [`Zone::Offset`]), so a time in the repeated hour when
+    daylight saving time ends is not ambiguous when it is applied again.
+*/
+pub(crate) fn timestamp_to_local_filetime(seconds: i64, nanos: u32) -> io::Result<FileTime> {
+    Local.timestamp_opt(seconds, nanos).single()
+        .map(FileTime::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "The file time is out of range."))
+}
+
+/**
+    Which of the times to set using [`set_file_time`].
+*/
+#[cfg(windows)]
+#[derive(PartialEq)]
+enum WhichTime {
+    Creation,
+    Accessed,
+    Modified,
+}
+
+/**
+    Set one of the times of a file on Windows.
+    If `follow` is false the times of a symbolic link itself are set.
+*/
+#[cfg(windows)]
+fn set_file_time(file: &Path, time: &FileTime, which: WhichTime, follow: bool) -> io::Result<()> {
+    let time = Some(*time);
+    let times = match which {
+        WhichTime::Creation => Times { created: time, ..Times::default() },
+        WhichTime::Accessed => Times { accessed: time, ..Times::default() },
+        WhichTime::Modified => Times { modified: time, ..Times::default() },
+    };
+    set_file_times(file, times, follow)
+}
+
+/**
+    Set the times of a file on Windows with a single handle. Times that are `None` are left unchanged.
+    If `follow` is false the times of a symbolic link itself are set.
+*/
+#[cfg(windows)]
+fn set_file_times(file: &Path, times: Times, follow: bool) -> io::Result<()> {
+    use std::fs::OpenOptions;
+    use std::os::windows::fs::OpenOptionsExt;
+    use winapi::um::winnt::FILE_WRITE_ATTRIBUTES;
+    // Defined in winbase.h
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    const FILE_FLAG_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+
+    // Backup semantics are needed to open a directory.
+    let mut flags = FILE_FLAG_BACKUP_SEMANTICS;
+    if !follow {
+        flags |= FILE_FLAG_OPEN_REPARSE_POINT;
     }
+    let handle = OpenOptions::new().access_mode(FILE_WRITE_ATTRIBUTES).custom_flags(flags).open(file)?;
+    set_times(&handle, times)
+}
+
+/**
+   Set the creation date of a file.
+
+   ## Params
+   file: &Path -> The path of the file to change.
+   create: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_creation_date, FileTime};
+   use std::path::Path;
+
+   set_creation_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021).unwrap());
+   ```
+*/
+#[cfg(windows)]
+pub fn set_creation_date(file: &Path, create: &FileTime) -> io::Result<()> {
+    set_file_time(file, create, WhichTime::Creation, true)
+}
+
+
+/**
+   Set the accessed date of a file.
+
+   ## Params
+   file: &Path -> The path of the file to change.
+   create: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_accessed_date, FileTime};
+   use std::path::Path;
+
+   set_accessed_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021).unwrap());
+   ```
+*/
+#[cfg(windows)]
+pub fn set_accessed_date(file: &Path, accessed: &FileTime) -> io::Result<()> {
+    set_file_time(file, accessed, WhichTime::Accessed, true)
+}
+
+/**
+   Set the modified date of a file.
+
+   ## Params
+   file: &Path -> The path of the file to change.
+   create: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_changed_date, FileTime};
+   use std::path::Path;
+
+   set_changed_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021).unwrap());
+   ```
+*/
+#[cfg(windows)]
+pub fn set_changed_date(file: &Path, changed: &FileTime) -> io::Result<()> {
+    set_file_time(file, changed, WhichTime::Modified, true)
+}
+
+/**
+   Set the creation date of a file without following symbolic links.
+   If the path is a symbolic link, the date of the link itself is changed.
+
+   ## Params
+   file: &Path -> The path of the file to change.
+   create: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+*/
+#[cfg(windows)]
+pub fn set_creation_date_nofollow(file: &Path, create: &FileTime) -> io::Result<()> {
+    set_file_time(file, create, WhichTime::Creation, false)
+}
+
+/**
+   Set the accessed date of a file without following symbolic links.
+   If the path is a symbolic link, the date of the link itself is changed.
+
+   ## Params
+   file: &Path -> The path of the file to change.
+   accessed: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+*/
+#[cfg(windows)]
+pub fn set_accessed_date_nofollow(file: &Path, accessed: &FileTime) -> io::Result<()> {
+    set_file_time(file, accessed, WhichTime::Accessed, false)
+}
+
+/**
+   Set the modified date of a file without following symbolic links.
+   If the path is a symbolic link, the date of the link itself is changed.
+
+   ## Params
+   file: &Path -> The path of the file to change.
+   changed: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+*/
+#[cfg(windows)]
+pub fn set_changed_date_nofollow(file: &Path, changed: &FileTime) -> io::Result<()> {
+    set_file_time(file, changed, WhichTime::Modified, false)
+}
+
+/**
+   Set the times of an open file in one call.
+   Setting the times through the file handle avoids opening the path again.
+
+   ## Params
+   file: &File -> The open file to change. (Must be opened with write access.) <br>
+   times: [`Times`] -> The times to set. Times that are `None` are left unchanged.
+
+   ## Returns
+   io::Result<()> -> An error if the times could not be set.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_times, Times, FileTime};
+   use std::fs::File;
+
+   let file = File::create("download.bin").unwrap();
+   set_times(&file, Times { modified: Some(FileTime::new(25, 12, 2021).unwrap()), ..Times::default() }).unwrap();
+   ```
+*/
+#[cfg(windows)]
+pub fn set_times(file: &File, times: Times) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use self::winapi::shared::minwindef::FILETIME;
+    use self::winapi::_core::ptr::null;
+    use winapi::um::fileapi::SetFileTime;
+
+    let to_filetime = |time: Option<FileTime>| -> io::Result<Option<FILETIME>> {
+        match time {
+            Some(time) => {
+                let ticks = filetime_to_ticks(&time)?;
+                Ok(Some(FILETIME { dwLowDateTime: ticks as u32, dwHighDateTime: (ticks >> 32) as u32 }))
+            }
+            None => Ok(None)
+        }
+    };
+    let created = to_filetime(times.created)?;
+    let accessed = to_filetime(times.accessed)?;
+    let modified = to_filetime(times.modified)?;
+    let pointer = |time: &Option<FILETIME>| time.as_ref().map_or(null(), |t| t as *const FILETIME);
+
+    let result = unsafe {
+        SetFileTime(file.as_raw_handle() as _, pointer(&created), pointer(&accessed), pointer(&modified))
+    };
+    if result == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/**
+    The seconds between the Windows epoch (1601) and the Unix epoch (1970).
+*/
+#[cfg(windows)]
+const WINDOWS_EPOCH_OFFSET: i64 = 11_644_473_600;
+
+/**
+    Convert a [`FileTime`] into the 100 nanosecond ticks of a Windows FILETIME.
+*/
+#[cfg(windows)]
+fn filetime_to_ticks(time: &FileTime) -> io::Result<u64> {
+    let date_time = time.to_datetime()?;
+    let seconds = date_time.timestamp() + WINDOWS_EPOCH_OFFSET;
+    if seconds < 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Windows does not support file times before 1601."));
+    }
+    Ok(seconds as u64 * 10_000_000 + (date_time.timestamp_subsec_nanos() / 100) as u64)
+}
+
+/**
+    Convert the 100 nanosecond ticks of a Windows FILETIME into a [`FileTime`] in local time,
+    keeping the UTC offset.
+*/
+#[cfg(windows)]
+fn ticks_to_filetime(ticks: u64) -> io::Result<FileTime> {
+    let seconds = (ticks / 10_000_000) as i64 - WINDOWS_EPOCH_OFFSET;
+    timestamp_to_local_filetime(seconds, ((ticks % 10_000_000) * 100) as u32)
+}
+
+/**
+   Get the creation date of a file.
+
+   ## Params
+   file: &Path -> The path of the file.
+
+   ## Returns
+   io::Result<[`FileTime`]> -> The creation date, or an error if the file could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::get_creation_date;
+   use std::path::Path;
+
+   let created = get_creation_date(Path::new("/test.txt"));
+   ```
+*/
+#[cfg(windows)]
+pub fn get_creation_date(file: &Path) -> io::Result<FileTime> {
+    use std::os::windows::fs::MetadataExt;
+    ticks_to_filetime(std::fs::metadata(file)?.creation_time())
+}
+
+/**
+   Get the accessed date of a file.
+
+   ## Params
+   file: &Path -> The path of the file.
+
+   ## Returns
+   io::Result<[`FileTime`]> -> The accessed date, or an error if the file could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::get_accessed_date;
+   use std::path::Path;
+
+   let accessed = get_accessed_date(Path::new("/test.txt"));
+   ```
+*/
+#[cfg(windows)]
+pub fn get_accessed_date(file: &Path) -> io::Result<FileTime> {
+    use std::os::windows::fs::MetadataExt;
+    ticks_to_filetime(std::fs::metadata(file)?.last_access_time())
+}
+
+/**
+   Get the modified date of a file.
+   This is the date set by [`set_changed_date`].
+
+   ## Params
+   file: &Path -> The path of the file.
+
+   ## Returns
+   io::Result<[`FileTime`]> -> The modified date, or an error if the file could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::get_modified_date;
+   use std::path::Path;
+
+   let modified = get_modified_date(Path::new("/test.txt"));
+   ```
+*/
+#[cfg(windows)]
+pub fn get_modified_date(file: &Path) -> io::Result<FileTime> {
+    use std::os::windows::fs::MetadataExt;
+    ticks_to_filetime(std::fs::metadata(file)?.last_write_time())
+}
+
+/**
+   Get the date the metadata of a file last changed.
+
+   **Note**: Windows does not expose this time, so this always returns an `Unsupported` error.
+
+   ## Params
+   file: &Path -> The path of the file.
+
+   ## Returns
+   io::Result<[`FileTime`]> -> Always an `Unsupported` error.
+*/
+#[cfg(windows)]
+pub fn get_changed_date(_file: &Path) -> io::Result<FileTime> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "The change time is not available on Windows."))
+}
+
+/*
+
+    Linux Section
+
+ */
+/**
+   Set the creation date of a file.
+
+   **Note**: The creation date cannot be set on Unix systems (Except on Mac),
+   so this always returns an `Unsupported` error. Use [`capabilities`](crate::metadata::capabilities)
+   to check beforehand.
+
+   ## Params
+   `file: &Path` -> The path of the file to change. <br>
+   `create: &[`FileTime`]` -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> Always an error of kind `Unsupported`.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_creation_date, FileTime};
+   use std::path::Path;
+
+   assert!(set_creation_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021).unwrap()).is_err());
+   ```
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn set_creation_date(_file: &Path, _create: &FileTime) -> io::Result<()> {
+    Err(creation_date_unsupported())
+}
+
+/**
+   Set the creation date of a file without following symbolic links.
+
+   **Note**: The creation date cannot be set on Unix systems (Except on Mac),
+   so this always returns an `Unsupported` error.
+
+   ## Params
+   `file: &Path` -> The path of the file to change. <br>
+   `create: &[`FileTime`]` -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> Always an error of kind `Unsupported`.
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn set_creation_date_nofollow(_file: &Path, _create: &FileTime) -> io::Result<()> {
+    Err(creation_date_unsupported())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn creation_date_unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "The creation date cannot be set on this platform.")
+}
+
+/**
+   Set the accessed date of a file.
+
+   The time is set natively with nanosecond precision.
+
+   ## Params
+   file: &Path -> The path of the file to change. <br>
+   create: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_accessed_date, FileTime};
+   use std::path::Path;
+
+   set_accessed_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021).unwrap());
+   ```
+*/
+#[cfg(unix)]
+pub fn set_accessed_date(file: &Path, create: &FileTime) -> io::Result<()> {
+    let accessed = filetime_to_timespec(create)?;
+    utimensat(file, [accessed, omit_timespec()], 0)
+}
+
+/**
+   Set the modified date of a file.
+
+   The time is set natively with nanosecond precision.
+
+   ## Params
+   file: &Path -> The path of the file to change. <br>
+   create: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_changed_date, FileTime};
+   use std::path::Path;
+
+   set_changed_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021).unwrap());
+   ```
+*/
+#[cfg(unix)]
+pub fn set_changed_date(file: &Path, create: &FileTime) -> io::Result<()> {
+    let modified = filetime_to_timespec(create)?;
+    utimensat(file, [omit_timespec(), modified], 0)
+}
+
+/**
+   Set the accessed date of a file without following symbolic links.
+   If the path is a symbolic link, the date of the link itself is changed.
+
+   ## Params
+   file: &Path -> The path of the file to change. <br>
+   accessed: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_accessed_date_nofollow, FileTime};
+   use std::path::Path;
+
+   set_accessed_date_nofollow(Path::new("/link"), &FileTime::new(25, 12, 2021).unwrap());
+   ```
+*/
+#[cfg(unix)]
+pub fn set_accessed_date_nofollow(file: &Path, accessed: &FileTime) -> io::Result<()> {
+    let accessed = filetime_to_timespec(accessed)?;
+    utimensat(file, [accessed, omit_timespec()], libc::AT_SYMLINK_NOFOLLOW)
+}
+
+/**
+   Set the modified date of a file without following symbolic links.
+   If the path is a symbolic link, the date of the link itself is changed.
+
+   ## Params
+   file: &Path -> The path of the file to change. <br>
+   changed: &[`FileTime`] -> The new file time for a file.
+
+   ## Returns
+   io::Result<()> -> An error if the file could not be found or modified.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::{set_changed_date_nofollow, FileTime};
+   use std::path::Path;
+
+   set_changed_date_nofollow(Path::new("/link"), &FileTime::new(25, 12, 2021).unwrap());
+   ```
+*/
+#[cfg(unix)]
+pub fn set_changed_date_nofollow(file: &Path, changed: &FileTime) -> io::Result<()> {
+    let modified = filetime_to_timespec(changed)?;
+    utimensat(file, [omit_timespec(), modified], libc::AT_SYMLINK_NOFOLLOW)
+}
+
+/**
+   Get the creation (birth) date of a file.
+
+   ## Params
+   file: &Path -> The path of the file.
+
+   ## Returns
+   io::Result<[`FileTime`]> -> The creation date. <br>
+   An `Unsupported` error is returned if the filesystem does not store the creation date.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::get_creation_date;
+   use std::path::Path;
+
+   let created = get_creation_date(Path::new("/test.txt"));
+   ```
+*/
+#[cfg(target_os = "linux")]
+pub fn get_creation_date(file: &Path) -> io::Result<FileTime> {
+    let stx = statx(file, 0, libc::STATX_BTIME)?;
+    if stx.stx_mask & libc::STATX_BTIME == 0 {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "The filesystem does not store the creation date."));
+    }
+    timestamp_to_local_filetime(stx.stx_btime.tv_sec, stx.stx_btime.tv_nsec)
+}
+
+/**
+   Get the creation (birth) date of a file.
+
+   ## Params
+   file: &Path -> The path of the file.
+
+   ## Returns
+   io::Result<[`FileTime`]> -> The creation date. <br>
+   An `Unsupported` error is returned if the filesystem does not store the creation date.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::get_creation_date;
+   use std::path::Path;
+
+   let created = get_creation_date(Path::new("/test.txt"));
+   ```
+*/
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn get_creation_date(file: &Path) -> io::Result<FileTime> {
+    let created = std::fs::metadata(file)?.created()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "The creation date is before 1970."))?;
+    timestamp_to_local_filetime(created.as_secs() as i64, created.subsec_nanos())
+}
+
+/**
+   Get the accessed date of a file.
+
+   ## Params
+   file: &Path -> The path of the file.
+
+   ## Returns
+   io::Result<[`FileTime`]> -> The accessed date, or an error if the file could not be read.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::get_accessed_date;
+   use std::path::Path;
+
+   let accessed = get_accessed_date(Path::new("/test.txt"));
+   ```
+*/
+#[cfg(unix)]
+pub fn get_accessed_date(file: &Path) -> io::Result<FileTime> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = std::fs::metadata(file)?;
+    timestamp_to_local_filetime(meta.atime(), meta.atime_nsec() as u32)
 }
 
 /**
-   Set the creation date of a file.
+   Get the modified date of a file.
+   This is the date set by [`set_changed_date`].
 
    ## Params
-   file: &Path -> The path of the file to change.
-   create: &[`FileTime`] -> The new file time for a file.
+   file: &Path -> The path of the file.
 
    ## Returns
-   bool -> True if successful, false if not.
-   False means that the file could not be found or modified. Check to makesure
-   the path is correct.
+   io::Result<[`FileTime`]> -> The modified date, or an error if the file could not be read.
 
    ## Examples
    ```rust
-   use system_extensions::metadata::time::{set_creation_date, FileTime};
+   use system_extensions::metadata::time::get_modified_date;
    use std::path::Path;
 
-   set_creation_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021));
+   let modified = get_modified_date(Path::new("/test.txt"));
    ```
 */
-#[cfg(windows)]
-pub fn set_creation_date(file: &Path, create: &FileTime) -> bool {
-    windows_imports!();
-    unsafe {
-        let mut file_handle: winnt::HANDLE = std::mem::zeroed();
-
-        let wide: Vec<u16> = OsStr::new(file.to_str().unwrap()).encode_wide().chain(once(0)).collect();
-        file_handle = winapi::um::fileapi::CreateFileW(wide.as_ptr(), GENERIC_WRITE,
-                                                       FILE_SHARE_READ | FILE_SHARE_WRITE, null_mut(), OPEN_EXISTING,
-                                                       FILE_ATTRIBUTE_NORMAL | FILE_WRITE_ATTRIBUTES, null_mut());
-        if file_handle == winapi::um::handleapi::INVALID_HANDLE_VALUE {
-            return false;
-        }
+#[cfg(unix)]
+pub fn get_modified_date(file: &Path) -> io::Result<FileTime> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = std::fs::metadata(file)?;
+    timestamp_to_local_filetime(meta.mtime(), meta.mtime_nsec() as u32)
+}
 
-        let system_time: *mut SYSTEMTIME = &mut SYSTEMTIME {
-            wYear: 0,
-            wMonth: 0,
-            wDayOfWeek: 0,
-            wDay: 0,
-            wHour: 0,
-            wMinute: 0,
-            wSecond: 0,
-            wMilliseconds: 0,
-        };
+/**
+   Get the date the metadata of a file last changed. (The inode `ctime`.)
 
-        sysinfoapi::GetSystemTime(system_time);
+   **Note**: This date is updated by the system and cannot be set.
 
-        // Convert FileTime to SystemTime
-        filetime_to_systime(system_time, create);
+   ## Params
+   file: &Path -> The path of the file.
 
-        let file_time: *mut FILETIME = &mut FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
-        SystemTimeToFileTime(system_time, file_time);
+   ## Returns
+   io::Result<[`FileTime`]> -> The changed date, or an error if the file could not be read.
 
-        SetFileTime(file_handle, file_time as *const FILETIME, null_mut(), null_mut());
-        CloseHandle(file_handle);
-    }
+   ## Examples
+   ```rust
+   use system_extensions::metadata::time::get_changed_date;
+   use std::path::Path;
+
+   let changed = get_changed_date(Path::new("/test.txt"));
+   ```
+*/
+#[cfg(unix)]
+pub fn get_changed_date(file: &Path) -> io::Result<FileTime> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = std::fs::metadata(file)?;
+    timestamp_to_local_filetime(meta.ctime(), meta.ctime_nsec() as u32)
+}
 
-    true
+/**
+    Call `statx` on a path, requesting the fields in the mask.
+*/
+#[cfg(target_os = "linux")]
+pub(crate) fn statx(file: &Path, flags: libc::c_int, mask: libc::c_uint) -> io::Result<libc::statx> {
+    let path = path_to_cstring(file)?;
+    let mut stx: libc::statx = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::statx(libc::AT_FDCWD, path.as_ptr(), flags | libc::AT_STATX_SYNC_AS_STAT, mask, &mut stx) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stx)
 }
 
+/**
+    Convert a path into a C string for use with libc.
+*/
+#[cfg(unix)]
+pub(crate) fn path_to_cstring(file: &Path) -> io::Result<std::ffi::CString> {
+    use std::os::unix::ffi::OsStrExt;
+
+    std::ffi::CString::new(file.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "The path contains a null byte."))
+}
 
 /**
-   Set the accessed date of a file.
+   Set the times of an open file in one call with `futimens`.
+   Setting the times through the file descriptor avoids opening the path again.
+
+   **Note**: The creation date cannot be set on Unix, an `Unsupported` error is returned
+   if it is provided.
 
    ## Params
-   file: &Path -> The path of the file to change.
-   create: &[`FileTime`] -> The new file time for a file.
+   file: &File -> The open file to change. <br>
+   times: [`Times`] -> The times to set. Times that are `None` are left unchanged.
 
    ## Returns
-   bool -> True if successful, false if not.
-   False means that the file could not be found or modified. Check to makesure
-   the path is correct.
+   io::Result<()> -> An error if the times could not be set.
 
    ## Examples
    ```rust
-   use system_extensions::metadata::time::{set_accessed_date, FileTime};
-   use std::path::Path;
+   use system_extensions::metadata::time::{set_times, Times, FileTime};
+   use std::fs::File;
 
-   set_accessed_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021));
+   let file = File::create("download.bin").unwrap();
+   set_times(&file, Times { modified: Some(FileTime::new(25, 12, 2021).unwrap()), ..Times::default() }).unwrap();
+   # std::fs::remove_file("download.bin").unwrap();
    ```
 */
-#[cfg(windows)]
-pub fn set_accessed_date(file: &Path, accessed: &FileTime) -> bool {
-    unsafe {
-        windows_imports!();
-        let mut file_handle: winnt::HANDLE = std::mem::zeroed();
-
-        let wide: Vec<u16> = OsStr::new(file.to_str().unwrap()).encode_wide().chain(once(0)).collect();
-        file_handle = winapi::um::fileapi::CreateFileW(wide.as_ptr(), GENERIC_WRITE,
-                                                       FILE_SHARE_READ | FILE_SHARE_WRITE, null_mut(), OPEN_EXISTING,
-                                                       FILE_ATTRIBUTE_NORMAL | FILE_WRITE_ATTRIBUTES, null_mut());
-
-        if file_handle == winapi::um::handleapi::INVALID_HANDLE_VALUE {
-            return false;
-        }
+#[cfg(unix)]
+pub fn set_times(file: &File, times: Times) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
 
-        let system_time: *mut SYSTEMTIME = &mut SYSTEMTIME {
-            wYear: 0,
-            wMonth: 0,
-            wDayOfWeek: 0,
-            wDay: 0,
-            wHour: 0,
-            wMinute: 0,
-            wSecond: 0,
-            wMilliseconds: 0,
-        };
+    if times.created.is_some() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "The creation date cannot be set on Unix."));
+    }
+    let to_timespec = |time: Option<FileTime>| match time {
+        Some(time) => filetime_to_timespec(&time),
+        None => Ok(omit_timespec())
+    };
+    let specs = [to_timespec(times.accessed)?, to_timespec(times.modified)?];
 
-        sysinfoapi::GetSystemTime(system_time);
+    let result = unsafe { libc::futimens(file.as_raw_fd(), specs.as_ptr()) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
 
-        // Convert FileTime to SystemTime
-        filetime_to_systime(system_time, accessed);
+/**
+    Set the accessed and modified times of a path with `utimensat`.
+*/
+#[cfg(unix)]
+fn utimensat(file: &Path, times: [libc::timespec; 2], flags: libc::c_int) -> io::Result<()> {
+    let path = path_to_cstring(file)?;
 
-        let file_time: *mut FILETIME = &mut FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
-        SystemTimeToFileTime(system_time, file_time);
+    let result = unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), flags) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
 
-        SetFileTime(file_handle, null_mut(), file_time as *const FILETIME, null_mut());
-        CloseHandle(file_handle);
+/**
+    A timespec that tells `utimensat` to leave the time unchanged.
+*/
+#[cfg(unix)]
+fn omit_timespec() -> libc::timespec {
+    libc::timespec {
+        tv_sec: 0,
+        tv_nsec: libc::UTIME_OMIT,
     }
-    true
 }
 
 /**
-   Set the modified date of a file.
+    Convert a [`FileTime`] to a timespec.
+    Fields that are not specified are taken from the current time.
+*/
+#[cfg(unix)]
+fn filetime_to_timespec(time: &FileTime) -> io::Result<libc::timespec> {
+    let date_time = time.to_datetime()?;
+    Ok(libc::timespec {
+        tv_sec: date_time.timestamp() as libc::time_t,
+        tv_nsec: date_time.timestamp_subsec_nanos() as libc::c_long,
+    })
+}
+
+/**
+   Convert the file time to the system time needed for unix.
+   <br>
+   This is the same as [`FileTime::to_touch_format`].
 
    ## Params
-   file: &Path -> The path of the file to change.
-   create: &[`FileTime`] -> The new file time for a file.
+   time: &[`FileTime`] -> The file time to format.
 
    ## Returns
-   bool -> True if successful, false if not.
-   False means that the file could not be found or modified. Check to makesure
-   the path is correct.
+   String -> The time in the `touch -t` format. (`[[CC]YY]MMDDhhmm[.ss]`)
 
    ## Examples
    ```rust
-   use system_extensions::metadata::time::{set_changed_date, FileTime};
-   use std::path::Path;
 
-   set_changed_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021));
+   if cfg!(unix) {
+       use system_extensions::metadata::time::{filetime_to_systime, FileTime};
+       use std::path::Path;
+       filetime_to_systime(&FileTime::new(25, 12, 2021).unwrap());
+   }
    ```
 */
-#[cfg(windows)]
-pub fn set_changed_date(file: &Path, changed: &FileTime) -> bool{
-   unsafe {
-       windows_imports!();
-       let mut file_handle: winnt::HANDLE = std::mem::zeroed();
-
-       let wide: Vec<u16> = OsStr::new(file.to_str().unwrap()).encode_wide().chain(once(0)).collect();
-       file_handle = winapi::um::fileapi::CreateFileW(wide.as_ptr(), GENERIC_WRITE,
-                                                      FILE_SHARE_READ | FILE_SHARE_WRITE, null_mut(), OPEN_EXISTING,
-                                                      FILE_ATTRIBUTE_NORMAL | FILE_WRITE_ATTRIBUTES, null_mut());
-
-       if file_handle == winapi::um::handleapi::INVALID_HANDLE_VALUE {
-           return false;
-       }
-
-       let system_time: *mut SYSTEMTIME = &mut SYSTEMTIME {
-           wYear: 0,
-           wMonth: 0,
-           wDayOfWeek: 0,
-           wDay: 0,
-           wHour: 0,
-           wMinute: 0,
-           wSecond: 0,
-           wMilliseconds: 0,
-       };
-
-       sysinfoapi::GetSystemTime(system_time);
-
-       // Convert FileTime to SystemTime
-       filetime_to_systime(system_time, changed);
-
-       let file_time: *mut FILETIME = &mut FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
-       SystemTimeToFileTime(system_time, file_time);
-
-       SetFileTime(file_handle, null_mut(), null_mut(), file_time as *const FILETIME);
-       CloseHandle(file_handle);
-   }
-
-    true
+#[cfg(unix)]
+pub fn filetime_to_systime(time: &FileTime) -> String{
+    time.to_touch_format()
 }
 
 /*
 
-    Linux Section
+    Mac specific section
 
  */
 /**
    Set the creation date of a file.
 
-   **Note**: This does nothing on Unix systems and only exists
-   for cross compatibility. (Except on Mac)
+   **Note:** Requires the `SetFile` command to be on the system.
 
    ## Params
    `file: &Path` -> The path of the file to change. <br>
    `create: &[`FileTime`]` -> The new file time for a file.
 
    ## Returns
-   bool -> True if successful, false if not.     <br>
-   False means that the file could not be found or modified. Check to makesure
-   the path is correct.
+   io::Result<()> -> An error of kind `Unsupported` if `SetFile` is not installed,
+   or an error if it failed to modify the file.
 
    ## Examples
    ```rust
    use system_extensions::metadata::time::{set_creation_date, FileTime};
    use std::path::Path;
 
-   set_creation_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021));
+   set_creation_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021).unwrap());
    ```
 */
-#[cfg(all(unix, not(target_os = "macos")))]
-pub fn set_creation_date(file: &Path, create: &FileTime) -> bool {
-    //Creation time is not stored by Unix
-    true
+#[cfg(target_os = "macos")]
+pub fn set_creation_date(file: &Path, create: &FileTime) -> io::Result<()> {
+    set_file_creation(file, create, true)
 }
 
 /**
-   Set the accessed date of a file.
+   Set the creation date of a file without following symbolic links.
+   If the path is a symbolic link, the date of the link itself is changed.
+
+   **Note:** Requires the `SetFile` command to be on the system.
 
    ## Params
-   file: &Path -> The path of the file to change. <br>
-   create: &[`FileTime`] -> The new file time for a file.
+   `file: &Path` -> The path of the file to change. <br>
+   `create: &[`FileTime`]` -> The new file time for a file.
 
    ## Returns
-   bool -> True if successful, false if not. <br>
-   False means that the file could not be found or modified. Check to makesure
-   the path is correct.
+   io::Result<()> -> An error of kind `Unsupported` if `SetFile` is not installed,
+   or an error if it failed to modify the file.
+*/
+#[cfg(target_os = "macos")]
+pub fn set_creation_date_nofollow(file: &Path, create: &FileTime) -> io::Result<()> {
+    set_file_creation(file, create, false)
+}
 
-   ## Examples
-   ```rust
-   use system_extensions::metadata::time::{set_accessed_date, FileTime};
-   use std::path::Path;
+#[cfg(target_os = "macos")]
+fn set_file_creation(file: &Path, create: &FileTime, follow: bool) -> io::Result<()> {
+    let mut command = Command::new("SetFile");
+    if !follow {
+        command.arg("-P");
+    }
+    let status = command
+        .arg("-d")
+        .arg(create.to_datetime()?.with_timezone(&Local).format("%m/%d/%Y %H:%M:%S").to_string())
+        .arg(file)
+        .status()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::new(io::ErrorKind::Unsupported,
+                "SetFile was not found, it is installed with the Xcode command line tools"),
+            _ => e
+        })?;
+
+    if !status.success() {
+        return Err(io::Error::new(io::ErrorKind::Other, format!("SetFile exited with {}", status)));
+    }
+    Ok(())
+}
 
-   set_accessed_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021));
-   ```
+/*
+
+    Cross platform section
+
+ */
+/**
+    How [`normalize_tree`] changes the times of each entry.
 */
-#[cfg(unix)]
-pub fn set_accessed_date(file: &Path, create: &FileTime) -> bool {
-  Command::new("touch").arg("-a").arg("-t").arg(filetime_to_systime(&create)).arg(file.to_str().unwrap()).spawn().is_ok()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizeMode {
+    /// Set every time to the target time.
+    Set,
+    /// Only change times that are newer than the target time. (Like `tar --clamp-mtime`.)
+    Clamp,
+}
+
+/**
+    The options for [`normalize_tree`].
+    This is a builder struct, all `set_` and `add_` functions return self.
+
+    Glob patterns support `*` (anything except `/`), `**` (anything) and `?` (one character).
+    Patterns without a `/` are matched against the file name, other patterns are matched
+    against the path relative to the directory being normalized, using `/` as the separator.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::time::{NormalizeOptions, NormalizeMode};
+
+    let mut options = NormalizeOptions::new();
+    options.set_mode(NormalizeMode::Clamp)
+        .add_exclude(".git")
+        .add_include("*.rs");
+    ```
+*/
+#[derive(Debug, Clone)]
+pub struct NormalizeOptions {
+    pub(crate) mode: NormalizeMode,
+    pub(crate) follow_symlinks: bool,
+    pub(crate) use_source_date_epoch: bool,
+    pub(crate) include: Vec<String>,
+    pub(crate) exclude: Vec<String>,
+}
+
+impl NormalizeOptions {
+    /**
+        Construct the default options.
+
+        By default every time is set, symbolic links are not followed (the times of the links
+        themselves are changed), `SOURCE_DATE_EPOCH` is used when present, and every entry is included.
+    */
+    pub fn new() -> NormalizeOptions {
+        NormalizeOptions {
+            mode: NormalizeMode::Set,
+            follow_symlinks: false,
+            use_source_date_epoch: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    /**
+        Set whether times are set or clamped.
+    */
+    pub fn set_mode(&mut self, mode: NormalizeMode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /**
+        Set whether symbolic links are followed.
+        When followed, the times of the targets are changed and linked directories are walked.
+    */
+    pub fn set_follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /**
+        Set whether the `SOURCE_DATE_EPOCH` environment variable replaces the provided time
+        when it is present.
+    */
+    pub fn set_use_source_date_epoch(&mut self, use_source_date_epoch: bool) -> &mut Self {
+        self.use_source_date_epoch = use_source_date_epoch;
+        self
+    }
+
+    /**
+        Only change entries matching the glob. (Directories are still walked.)
+        Can be called multiple times, an entry is included if it matches any glob.
+    */
+    pub fn add_include(&mut self, glob: &str) -> &mut Self {
+        self.include.push(glob.to_string());
+        self
+    }
+
+    /**
+        Skip entries matching the glob. An excluded directory is not walked.
+        Can be called multiple times, an entry is excluded if it matches any glob.
+        The directory being normalized is never excluded.
+    */
+    pub fn add_exclude(&mut self, glob: &str) -> &mut Self {
+        self.exclude.push(glob.to_string());
+        self
+    }
+}
 
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        NormalizeOptions::new()
+    }
 }
 
 /**
-   Set the modified date of a file.
+   Set or clamp the accessed and modified dates of every entry in a directory tree.
+   This is used to create reproducible archives.
+
+   If the `SOURCE_DATE_EPOCH` environment variable is present (and enabled in the options)
+   its value is used instead of the provided time.
+   The directory itself is included unless include globs are provided. The globs are only
+   matched against the entries inside the directory, so exclude globs never skip the directory itself.
 
    ## Params
-   file: &Path -> The path of the file to change. <br>
-   create: &[`FileTime`] -> The new file time for a file.
+   dir: &Path -> The directory to normalize. <br>
+   time: &[`FileTime`] -> The time to set or clamp to. <br>
+   options: &[`NormalizeOptions`] -> How to walk the tree.
 
    ## Returns
-   bool -> True if successful, false if not. <br>
-   False means that the file could not be found or modified. Check to makesure
-   the path is correct.
+   io::Result<usize> -> The number of entries that were changed, or the first error that occurred.
 
    ## Examples
    ```rust
-   use system_extensions::metadata::time::{set_changed_date, FileTime};
+   use system_extensions::metadata::time::{normalize_tree, FileTime, NormalizeOptions};
    use std::path::Path;
 
-   set_changed_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021));
+   let changed = normalize_tree(Path::new("./build"), &FileTime::new(1, 1, 2020).unwrap(), &NormalizeOptions::new());
    ```
 */
+pub fn normalize_tree(dir: &Path, time: &FileTime, options: &NormalizeOptions) -> io::Result<usize> {
+    let time = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) if options.use_source_date_epoch => {
+            let seconds = epoch.trim().parse::<i64>()
+                .map_err(|_| invalid_input(format!("SOURCE_DATE_EPOCH '{}' is not a number.", epoch)))?;
+            timestamp_to_utc_filetime(seconds, 0)
+                .map_err(|_| invalid_input(format!("SOURCE_DATE_EPOCH '{}' is out of range.", epoch)))?
+        }
+        _ => *time
+    };
+    let target = time.to_system_time()?;
+
+    let mut changed = 0;
+    let mut visited = std::collections::HashSet::new();
+    visited.insert(std::fs::canonicalize(dir)?);
+    normalize_dir(dir, "", &time, target, options, &mut visited, &mut changed)?;
+    if options.include.is_empty() {
+        changed += normalize_entry(dir, &time, target, options, true)? as usize;
+    }
+    Ok(changed)
+}
+
+/**
+    Walk a directory for [`normalize_tree`].
+*/
+fn normalize_dir(dir: &Path, relative: &str, time: &FileTime, target: SystemTime, options: &NormalizeOptions,
+                 visited: &mut std::collections::HashSet<std::path::PathBuf>, changed: &mut usize) -> io::Result<()> {
+    let mut entries = std::fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    // Walk in a stable order so errors are reproducible.
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = if relative.is_empty() { name.clone() } else { format!("{}/{}", relative, name) };
+        let path = entry.path();
+
+        if options.exclude.iter().any(|glob| glob_matches(glob, &name, &relative)) {
+            continue;
+        }
+
+        // Directories are walked before their own times are changed, since listing
+        // a directory can update its accessed date.
+        let file_type = entry.file_type()?;
+        let is_dir = file_type.is_dir() || (file_type.is_symlink() && options.follow_symlinks && path.is_dir());
+        // Following links can create loops, so every directory is only walked once.
+        if is_dir && visited.insert(std::fs::canonicalize(&path)?) {
+            normalize_dir(&path, &relative, time, target, options, visited, changed)?;
+        }
+
+        if options.include.is_empty() || options.include.iter().any(|glob| glob_matches(glob, &name, &relative)) {
+            *changed += normalize_entry(&path, time, target, options, options.follow_symlinks)? as usize;
+        }
+    }
+    Ok(())
+}
+
+/**
+    Set or clamp the times of a single entry. Returns if the entry was changed.
+*/
+fn normalize_entry(path: &Path, time: &FileTime, target: SystemTime, options: &NormalizeOptions, follow: bool) -> io::Result<bool> {
+    let meta = if follow { std::fs::metadata(path)? } else { std::fs::symlink_metadata(path)? };
+    let (accessed, modified) = match options.mode {
+        NormalizeMode::Set => (Some(*time), Some(*time)),
+        NormalizeMode::Clamp => (
+            if meta.accessed()? > target { Some(*time) } else { None },
+            if meta.modified()? > target { Some(*time) } else { None }
+        ),
+    };
+    if accessed.is_none() && modified.is_none() {
+        return Ok(false);
+    }
+    set_accessed_and_modified(path, accessed, modified, follow)?;
+    Ok(true)
+}
+
+/**
+    Set the accessed and modified dates of a path at once. Dates that are `None` are left unchanged.
+*/
 #[cfg(unix)]
-pub fn set_changed_date(file: &Path, create: &FileTime) -> bool {
-    Command::new("touch").arg("-m").arg("-t").arg(filetime_to_systime(&create)).arg(file.to_str().unwrap()).spawn().is_ok()
+fn set_accessed_and_modified(path: &Path, accessed: Option<FileTime>, modified: Option<FileTime>, follow: bool) -> io::Result<()> {
+    let to_timespec = |time: Option<FileTime>| match time {
+        Some(time) => filetime_to_timespec(&time),
+        None => Ok(omit_timespec())
+    };
+    let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+    utimensat(path, [to_timespec(accessed)?, to_timespec(modified)?], flags)
 }
 
 /**
-   Convert the file time to the system time needed for unix.
-   <br>
-   This is primarily used internally.
+    Set the accessed and modified dates of a path at once. Dates that are `None` are left unchanged.
+*/
+#[cfg(windows)]
+fn set_accessed_and_modified(path: &Path, accessed: Option<FileTime>, modified: Option<FileTime>, follow: bool) -> io::Result<()> {
+    set_file_times(path, Times { accessed, modified, created: None }, follow)
+}
+
+/**
+   Move the accessed and/or modified dates of many files forward or back by an offset,
+   like `touch -d '+2 hours'`. Sub-second parts of the dates are preserved.
+
+   Every path is shifted even if some of them fail, so the shift is never applied twice
+   to part of the files when retrying the failures.
 
    ## Params
-   time: &[`FileTime`] -> The new file time for a file.
+   paths: IntoIterator<AsRef<Path>> -> The files to shift. <br>
+   offset: chrono::Duration -> The offset to shift by. (Negative to move back.) <br>
+   which: [`Which`] -> The dates to shift.
 
    ## Returns
-   bool -> True if successful, false if not.
-   False means that the file could not be found or modified. Check to makesure
-   the path is correct.
+   Result<(), Vec<(PathBuf, io::Error)>> -> The paths that could not be shifted along with their errors.
 
    ## Examples
    ```rust
+   use system_extensions::metadata::time::{shift_times, Which};
+   use std::path::Path;
 
-   if cfg!(unix) {
-       use system_extensions::metadata::time::{filetime_to_systime, FileTime};
-       use std::path::Path;
-       filetime_to_systime(&FileTime::new(25, 12, 2021));
-   }
+   let result = shift_times(&[Path::new("./IMG_0001.jpg")], chrono::Duration::hours(-2), Which::Both);
    ```
 */
-#[cfg(unix)]
-pub fn filetime_to_systime(time: &FileTime) -> String{
-    use chrono::{Local, Timelike, Datelike};
+pub fn shift_times<I, P>(paths: I, offset: Duration, which: Which) -> Result<(), Vec<(std::path::PathBuf, io::Error)>>
+    where I: IntoIterator<Item=P>, P: AsRef<Path> {
+    let mut failures = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        if let Err(error) = shift_time(path, offset, which) {
+            failures.push((path.to_path_buf(), error));
+        }
+    }
+    if failures.is_empty() { Ok(()) } else { Err(failures) }
+}
+
+/**
+    Shift the dates of a single file for [`shift_times`].
+*/
+fn shift_time(path: &Path, offset: Duration, which: Which) -> io::Result<()> {
+    let meta = std::fs::metadata(path)?;
+    let shift = |time: SystemTime| -> io::Result<FileTime> {
+        let date_time: DateTime<Utc> = DateTime::from(time);
+        let shifted = date_time.checked_add_signed(offset)
+            .ok_or_else(|| invalid_input(format!("Shifting {} by {} is out of range.", date_time, offset)))?;
+        system_time_to_utc_filetime(shifted.into())
+    };
+    let accessed = if which != Which::Modified { Some(shift(meta.accessed()?)?) } else { None };
+    let modified = if which != Which::Accessed { Some(shift(meta.modified()?)?) } else { None };
+    set_accessed_and_modified(path, accessed, modified, true)
+}
 
-    let now = Local::now();
+/**
+    A guard that captures the dates of a file and restores them when it is dropped,
+    even if the code editing the file panics.
+
+    The accessed and modified dates are captured with full precision. The creation date
+    is also captured and restored on Windows.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::time::PreserveTimes;
+    use std::path::Path;
+    use std::fs;
+
+    # fs::write("song.mp3", b"ID3").unwrap();
+    {
+        let _guard = PreserveTimes::new(Path::new("song.mp3")).unwrap();
+        fs::write("song.mp3", b"ID3 with new tags").unwrap();
+    } // The dates of song.mp3 are restored here.
+    # fs::remove_file("song.mp3").unwrap();
+    ```
+*/
+#[derive(Debug)]
+pub struct PreserveTimes {
+    path: std::path::PathBuf,
+    accessed: FileTime,
+    modified: FileTime,
+    created: Option<FileTime>,
+    active: bool,
+}
 
-    let mut year:String;
-    if time.year!=-1{
-        year = time.year.to_string();
-    }else{
-        year = now.year().to_string();
-    }
-    if year.len()!=2{
-        year = format!("{}", year);
-    }
+impl PreserveTimes {
+    /**
+        Capture the dates of a file.
 
-    let mut month:String;
-    if time.month!=-1{
-        month = time.month.to_string();
-    }else{
-        month = now.month().to_string();
-    }
-    if month.len()!=2{
-        month = format!("0{}", month);
+        # Params
+        path: &Path -> The file to preserve the dates of.
+
+        # Returns
+        io::Result<PreserveTimes> -> The guard, or an error if the dates could not be read.
+    */
+    pub fn new(path: &Path) -> io::Result<PreserveTimes> {
+        let meta = std::fs::metadata(path)?;
+        let created = if cfg!(windows) {
+            Some(system_time_to_utc_filetime(meta.created()?)?)
+        } else {
+            None
+        };
+        Ok(PreserveTimes {
+            path: path.to_path_buf(),
+            accessed: system_time_to_utc_filetime(meta.accessed()?)?,
+            modified: system_time_to_utc_filetime(meta.modified()?)?,
+            created,
+            active: true,
+        })
     }
 
-    let mut day:String;
-    if time.day!=-1{
-        day = time.day.to_string();
-    }else{
-        day = (now.day()).to_string();
+    /// The captured accessed date.
+    pub fn accessed(&self) -> FileTime {
+        self.accessed
     }
-    if day.len()!=2{
-        day = format!("0{}", day);
+
+    /// The captured modified date.
+    pub fn modified(&self) -> FileTime {
+        self.modified
     }
 
-    let mut hour:String;
-    if time.hour!=-1{
-        hour = time.hour.to_string();
-    }else{
-        hour = (now.hour()+1).to_string();
+    /// The captured creation date. (Only captured on Windows.)
+    pub fn created(&self) -> Option<FileTime> {
+        self.created
     }
-    if hour.len()!=2{
-        hour = format!("0{}", hour);
+
+    /**
+        Restore the captured dates now, reporting any error.
+        The dates are restored again when the guard is dropped.
+    */
+    pub fn restore(&self) -> io::Result<()> {
+        #[cfg(windows)]
+        {
+            let times = Times { accessed: Some(self.accessed), modified: Some(self.modified), created: self.created };
+            set_file_times(&self.path, times, true)
+        }
+        #[cfg(not(windows))]
+        {
+            set_accessed_and_modified(&self.path, Some(self.accessed), Some(self.modified), true)
+        }
     }
 
-    let mut minute:String;
-    if time.minute!=-1{
-        minute = time.minute.to_string();
-    }else{
-        minute = (now.minute()+1).to_string();
+    /**
+        Drop the guard without restoring the dates.
+    */
+    pub fn forget(mut self) {
+        self.active = false;
     }
-    if minute.len()!=2{
-        minute = format!("0{}", minute);
+}
+
+impl Drop for PreserveTimes {
+    fn drop(&mut self) {
+        if self.active {
+            // Errors cannot be reported from drop, use restore() to handle them.
+            let _ = self.restore();
+        }
     }
+}
 
-    let mut second:String;
-    if time.second!=-1{
-        second = time.second.to_string();
-    }else{
-        second = (now.second()+1).to_string();
+/**
+    Copy the accessed and modified dates (and the creation date on Windows) of one file to another.
+*/
+pub(crate) fn copy_times(src: &Path, dst: &Path, follow: bool) -> io::Result<()> {
+    let meta = if follow { std::fs::metadata(src)? } else { std::fs::symlink_metadata(src)? };
+    let accessed = system_time_to_utc_filetime(meta.accessed()?)?;
+    let modified = system_time_to_utc_filetime(meta.modified()?)?;
+    #[cfg(windows)]
+    {
+        let created = system_time_to_utc_filetime(meta.created()?)?;
+        let times = Times { accessed: Some(accessed), modified: Some(modified), created: Some(created) };
+        set_file_times(dst, times, follow)
     }
-    if second.len()!=2{
-        second = format!("0{}", second);
+    #[cfg(not(windows))]
+    {
+        set_accessed_and_modified(dst, Some(accessed), Some(modified), follow)
     }
-
-    format!("{}{}{}{}{}.{}", year, month, day, hour, minute, second)
 }
 
-/*
-
-    Mac specific section
-
- */
 /**
-   Set the creation date of a file.
-
-   **Note:** Requires the `SetFile` command to be on the system.
-
-   ## Params
-   `file: &Path` -> The path of the file to change. <br>
-   `create: &[`FileTime`]` -> The new file time for a file.
+    Convert a system time into a [`FileTime`] in UTC.
+*/
+fn system_time_to_utc_filetime(time: SystemTime) -> io::Result<FileTime> {
+    let date_time: DateTime<Utc> = DateTime::from(time);
+    timestamp_to_utc_filetime(date_time.timestamp(), date_time.timestamp_subsec_nanos())
+}
 
-   ## Returns
-   bool -> True if successful, false if not.     <br>
-   False means that the file could not be found or modified. Check to makesure
-   the path is correct.
+/**
+    Check if a glob matches an entry.
+    Globs without a `/` are matched against the name, others against the relative path.
+*/
+pub(crate) fn glob_matches(glob: &str, name: &str, relative: &str) -> bool {
+    let text = if glob.contains('/') { relative } else { name };
+    glob_match(glob.as_bytes(), text.as_bytes())
+}
 
-   ## Examples
-   ```rust
-   use system_extensions::metadata::time::{set_creation_date, FileTime};
-   use std::path::Path;
+/**
+    A part of a glob.
+*/
+#[derive(Clone, Copy)]
+enum GlobPart {
+    Char(u8),
+    /// `?`, any character but `/`.
+    Any,
+    /// `*`, anything within a directory.
+    Star,
+    /// `**`, anything including `/`.
+    GlobStar,
+    /// `**/`, any number of whole directories (including none).
+    Dirs,
+}
 
-   set_creation_date(Path::new("/test.txt"), &FileTime::new(25, 12, 2021));
-   ```
+/**
+    Match a glob by keeping every part of the glob that the text read so far can reach,
+    which takes `glob.len() * text.len()` steps at most. (Backtracking is exponential on many stars.)
 */
-#[cfg(target_os = "macos")]
-pub fn set_creation_date(file: &Path, create: &FileTime) -> bool {
-    let mut time = "AM";
-    if create.hour >= 12 {
-        time = "PM";
+fn glob_match(glob: &[u8], text: &[u8]) -> bool {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < glob.len() {
+        let (part, length) = match glob[i..] {
+            [b'*', b'*', b'/', ..] => (GlobPart::Dirs, 3),
+            [b'*', b'*', ..] => (GlobPart::GlobStar, 2),
+            [b'*', ..] => (GlobPart::Star, 1),
+            [b'?', ..] => (GlobPart::Any, 1),
+            [c, ..] => (GlobPart::Char(c), 1),
+            [] => unreachable!(),
+        };
+        parts.push(part);
+        i += length;
     }
 
-    Command::new("SetFile")
-        .arg("-d")
-        .arg(format!("'{}/{}/{} {}:{}:{} {}'", create.month, create.day, (create.year + 1), create.hour,
-                     create.minute, create.second, time))
-        .arg(file.to_str().unwrap())
-        .spawn().is_ok()
-}
\ No newline at end of file
+    // The stars can also match nothing, so the parts after them are reachable too.
+    let close = |at: &mut Vec<bool>| {
+        for i in 0..parts.len() {
+            if at[i] && matches!(parts[i], GlobPart::Star | GlobPart::GlobStar | GlobPart::Dirs) {
+                at[i + 1] = true;
+            }
+        }
+    };
+    // `at[i]` is set if part `i` is next, `in_dir[i]` if the `**/` part `i` is in the middle of a directory name.
+    let mut at = vec![false; parts.len() + 1];
+    let mut in_dir = vec![false; parts.len()];
+    at[0] = true;
+    close(&mut at);
+    for &c in text {
+        let mut next_at = vec![false; parts.len() + 1];
+        let mut next_in_dir = vec![false; parts.len()];
+        for (i, part) in parts.iter().enumerate() {
+            if in_dir[i] || (at[i] && matches!(part, GlobPart::Dirs)) {
+                if c == b'/' { next_at[i] = true } else { next_in_dir[i] = true }
+            }
+            if !at[i] {
+                continue;
+            }
+            match *part {
+                GlobPart::Char(expected) => next_at[i + 1] |= c == expected,
+                GlobPart::Any => next_at[i + 1] |= c != b'/',
+                GlobPart::Star => next_at[i] |= c != b'/',
+                GlobPart::GlobStar => next_at[i] = true,
+                GlobPart::Dirs => {}
+            }
+        }
+        close(&mut next_at);
+        if !next_at.contains(&true) && !next_in_dir.contains(&true) {
+            return false;
+        }
+        at = next_at;
+        in_dir = next_in_dir;
+    }
+    at[parts.len()]
+}
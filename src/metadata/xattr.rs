@@ -0,0 +1,512 @@
+use std::error::Error;
+use std::ffi::{CString, OsStr};
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
+
+use libc::{c_char, c_void, ssize_t};
+
+/**
+    The namespace of an extended attribute on Linux.
+    The namespace is the part of the name before the first '.'. (ex: `user` in `user.comment`)
+
+    **Note:** macOS does not have namespaces, names are stored exactly as given.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::xattr::Namespace;
+
+    assert_eq!(Namespace::User.attribute("comment"), "user.comment");
+    assert_eq!(Namespace::of("trusted.md5"), Some(Namespace::Trusted));
+    ```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    /// Attributes that any user with access to the file can use.
+    User,
+    /// Attributes that only processes with `CAP_SYS_ADMIN` can see and change.
+    Trusted,
+    /// Attributes used by security modules. (ex: `security.selinux`, `security.capability`)
+    Security,
+    /// Attributes used by the kernel. (ex: `system.posix_acl_access`)
+    System,
+}
+
+impl Namespace {
+    /**
+        Get the prefix of the namespace, without the '.'.
+    */
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Namespace::User => "user",
+            Namespace::Trusted => "trusted",
+            Namespace::Security => "security",
+            Namespace::System => "system",
+        }
+    }
+
+    /**
+        Get the full name of an attribute in this namespace.
+
+        # Params
+        key: &str -> The name of the attribute without the namespace.<br>
+
+        # Returns
+        String -> The full name. (ex: `user.key`)
+    */
+    pub fn attribute(self, key: &str) -> String {
+        format!("{}.{}", self.prefix(), key)
+    }
+
+    /**
+        Get the namespace of a full attribute name.
+
+        # Params
+        name: &str -> The full name of the attribute.<br>
+
+        # Returns
+        Option<Namespace> -> The namespace, or None if the name is not in a known namespace.
+    */
+    pub fn of(name: &str) -> Option<Namespace> {
+        match name.split('.').next()? {
+            "user" => Some(Namespace::User),
+            "trusted" => Some(Namespace::Trusted),
+            "security" => Some(Namespace::Security),
+            "system" => Some(Namespace::System),
+            _ => None,
+        }
+    }
+}
+
+/**
+    An error from an extended attribute operation.
+*/
+#[derive(Debug)]
+pub enum XattrError {
+    /// The attribute does not exist on the file. (`ENODATA` / `ENOATTR`)
+    NoData,
+    /// The filesystem does not support extended attributes, or not in this namespace. (`ENOTSUP`)
+    NotSupported,
+    /// Any other error, such as the file not existing or missing permissions.
+    Io(io::Error),
+}
+
+impl fmt::Display for XattrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XattrError::NoData => write!(f, "The extended attribute does not exist"),
+            XattrError::NotSupported => write!(f, "Extended attributes are not supported by the filesystem"),
+            XattrError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for XattrError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            XattrError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for XattrError {
+    fn from(error: io::Error) -> Self {
+        match error.raw_os_error() {
+            Some(ENOATTR) => XattrError::NoData,
+            Some(libc::ENOTSUP) => XattrError::NotSupported,
+            _ => XattrError::Io(error),
+        }
+    }
+}
+
+impl From<XattrError> for io::Error {
+    fn from(error: XattrError) -> Self {
+        match error {
+            XattrError::NoData => io::Error::from_raw_os_error(ENOATTR),
+            XattrError::NotSupported => io::Error::from_raw_os_error(libc::ENOTSUP),
+            XattrError::Io(e) => e,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+const ENOATTR: i32 = libc::ENODATA;
+#[cfg(not(target_os = "linux"))]
+const ENOATTR: i32 = libc::ENOATTR;
+
+/// What an extended attribute operation is run on.
+enum Target {
+    Path(CString, bool),
+    Fd(RawFd),
+}
+
+impl Target {
+    fn path(path: &Path, follow: bool) -> Result<Target, XattrError> {
+        Ok(Target::Path(to_cstring(path.as_os_str())?, follow))
+    }
+}
+
+fn to_cstring(value: &OsStr) -> Result<CString, XattrError> {
+    CString::new(value.as_bytes())
+        .map_err(|_| XattrError::Io(io::Error::new(io::ErrorKind::InvalidInput, "The value contains a null byte")))
+}
+
+/**
+    Get the value of an extended attribute.
+
+    # Params
+    path: &Path -> The path of the file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+
+    # Returns
+    Result<Vec<u8>, [`XattrError`]> -> The value of the attribute.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::xattr::{get, XattrError};
+    use std::path::Path;
+
+    match get(Path::new("./test.txt"), "user.comment") {
+        Ok(value) => println!("{}", String::from_utf8_lossy(&value)),
+        Err(XattrError::NoData) => println!("No comment"),
+        Err(e) => println!("{}", e),
+    }
+    ```
+*/
+pub fn get(path: &Path, name: &str) -> Result<Vec<u8>, XattrError> {
+    get_target(&Target::path(path, true)?, name)
+}
+
+/**
+    Get the value of an extended attribute without following symbolic links.
+    If the path is a symbolic link, the attribute of the link itself is read.
+
+    # Params
+    path: &Path -> The path of the file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+
+    # Returns
+    Result<Vec<u8>, [`XattrError`]> -> The value of the attribute.
+*/
+pub fn get_nofollow(path: &Path, name: &str) -> Result<Vec<u8>, XattrError> {
+    get_target(&Target::path(path, false)?, name)
+}
+
+/**
+    Get the value of an extended attribute of an open file.
+
+    # Params
+    file: &File -> The open file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+
+    # Returns
+    Result<Vec<u8>, [`XattrError`]> -> The value of the attribute.
+*/
+pub fn get_fd(file: &File, name: &str) -> Result<Vec<u8>, XattrError> {
+    get_target(&Target::Fd(file.as_raw_fd()), name)
+}
+
+/**
+    Set the value of an extended attribute, creating it if it does not exist.
+
+    # Params
+    path: &Path -> The path of the file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+    value: &[u8] -> The new value.<br>
+
+    # Returns
+    Result<(), [`XattrError`]> -> An error if the attribute could not be set.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::xattr::set;
+    use std::path::Path;
+
+    set(Path::new("./test.txt"), "user.comment", b"Downloaded from example.com");
+    ```
+*/
+pub fn set(path: &Path, name: &str, value: &[u8]) -> Result<(), XattrError> {
+    set_target(&Target::path(path, true)?, name, value)
+}
+
+/**
+    Set the value of an extended attribute without following symbolic links.
+
+    **Note:** Linux does not allow `user` attributes on symbolic links.
+
+    # Params
+    path: &Path -> The path of the file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+    value: &[u8] -> The new value.<br>
+
+    # Returns
+    Result<(), [`XattrError`]> -> An error if the attribute could not be set.
+*/
+pub fn set_nofollow(path: &Path, name: &str, value: &[u8]) -> Result<(), XattrError> {
+    set_target(&Target::path(path, false)?, name, value)
+}
+
+/**
+    Set the value of an extended attribute of an open file.
+
+    # Params
+    file: &File -> The open file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+    value: &[u8] -> The new value.<br>
+
+    # Returns
+    Result<(), [`XattrError`]> -> An error if the attribute could not be set.
+*/
+pub fn set_fd(file: &File, name: &str, value: &[u8]) -> Result<(), XattrError> {
+    set_target(&Target::Fd(file.as_raw_fd()), name, value)
+}
+
+/**
+    List the names of the extended attributes of a file.
+
+    Only the attributes the process is allowed to see are listed.
+
+    # Params
+    path: &Path -> The path of the file.<br>
+
+    # Returns
+    Result<Vec<String>, [`XattrError`]> -> The full names of the attributes.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::xattr::{list, Namespace};
+    use std::path::Path;
+
+    if let Ok(names) = list(Path::new("./test.txt")) {
+        for name in names.iter().filter(|n| Namespace::of(n) == Some(Namespace::User)) {
+            println!("{}", name);
+        }
+    }
+    ```
+*/
+pub fn list(path: &Path) -> Result<Vec<String>, XattrError> {
+    list_target(&Target::path(path, true)?)
+}
+
+/**
+    List the names of the extended attributes of a file without following symbolic links.
+
+    # Params
+    path: &Path -> The path of the file.<br>
+
+    # Returns
+    Result<Vec<String>, [`XattrError`]> -> The full names of the attributes.
+*/
+pub fn list_nofollow(path: &Path) -> Result<Vec<String>, XattrError> {
+    list_target(&Target::path(path, false)?)
+}
+
+/**
+    List the names of the extended attributes of an open file.
+
+    # Params
+    file: &File -> The open file.<br>
+
+    # Returns
+    Result<Vec<String>, [`XattrError`]> -> The full names of the attributes.
+*/
+pub fn list_fd(file: &File) -> Result<Vec<String>, XattrError> {
+    list_target(&Target::Fd(file.as_raw_fd()))
+}
+
+/**
+    Remove an extended attribute.
+
+    # Params
+    path: &Path -> The path of the file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+
+    # Returns
+    Result<(), [`XattrError`]> -> [`XattrError::NoData`] if the attribute does not exist.
+*/
+pub fn remove(path: &Path, name: &str) -> Result<(), XattrError> {
+    remove_target(&Target::path(path, true)?, name)
+}
+
+/**
+    Remove an extended attribute without following symbolic links.
+
+    # Params
+    path: &Path -> The path of the file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+
+    # Returns
+    Result<(), [`XattrError`]> -> [`XattrError::NoData`] if the attribute does not exist.
+*/
+pub fn remove_nofollow(path: &Path, name: &str) -> Result<(), XattrError> {
+    remove_target(&Target::path(path, false)?, name)
+}
+
+/**
+    Remove an extended attribute of an open file.
+
+    # Params
+    file: &File -> The open file.<br>
+    name: &str -> The full name of the attribute. (ex: `user.comment`)<br>
+
+    # Returns
+    Result<(), [`XattrError`]> -> [`XattrError::NoData`] if the attribute does not exist.
+*/
+pub fn remove_fd(file: &File, name: &str) -> Result<(), XattrError> {
+    remove_target(&Target::Fd(file.as_raw_fd()), name)
+}
+
+fn get_target(target: &Target, name: &str) -> Result<Vec<u8>, XattrError> {
+    let name = to_cstring(OsStr::new(name))?;
+    read_sized(|buf, size| raw_get(target, &name, buf as *mut c_void, size))
+}
+
+fn set_target(target: &Target, name: &str, value: &[u8]) -> Result<(), XattrError> {
+    let name = to_cstring(OsStr::new(name))?;
+    check(raw_set(target, &name, value))
+}
+
+fn list_target(target: &Target) -> Result<Vec<String>, XattrError> {
+    let names = read_sized(|buf, size| raw_list(target, buf as *mut c_char, size))?;
+    Ok(names.split(|b| *b == 0)
+        .filter(|n| !n.is_empty())
+        .map(|n| String::from_utf8_lossy(n).into_owned())
+        .collect())
+}
+
+fn remove_target(target: &Target, name: &str) -> Result<(), XattrError> {
+    let name = to_cstring(OsStr::new(name))?;
+    check(raw_remove(target, &name))
+}
+
+/// Query the size of a value, then read it, retrying if it grew in between.
+fn read_sized<F: Fn(*mut u8, usize) -> ssize_t>(read: F) -> Result<Vec<u8>, XattrError> {
+    loop {
+        let size = read(std::ptr::null_mut(), 0);
+        if size < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        let mut buf = vec![0u8; size as usize];
+        let read_size = read(buf.as_mut_ptr(), buf.len());
+        if read_size >= 0 {
+            buf.truncate(read_size as usize);
+            return Ok(buf);
+        }
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(libc::ERANGE) {
+            return Err(error.into());
+        }
+    }
+}
+
+fn check(result: i32) -> Result<(), XattrError> {
+    if result != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/*
+
+    Linux Section
+
+ */
+#[cfg(target_os = "linux")]
+fn raw_get(target: &Target, name: &CString, buf: *mut c_void, size: usize) -> ssize_t {
+    unsafe {
+        match target {
+            Target::Path(path, true) => libc::getxattr(path.as_ptr(), name.as_ptr(), buf, size),
+            Target::Path(path, false) => libc::lgetxattr(path.as_ptr(), name.as_ptr(), buf, size),
+            Target::Fd(fd) => libc::fgetxattr(*fd, name.as_ptr(), buf, size),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn raw_set(target: &Target, name: &CString, value: &[u8]) -> i32 {
+    let data = value.as_ptr() as *const c_void;
+    unsafe {
+        match target {
+            Target::Path(path, true) => libc::setxattr(path.as_ptr(), name.as_ptr(), data, value.len(), 0),
+            Target::Path(path, false) => libc::lsetxattr(path.as_ptr(), name.as_ptr(), data, value.len(), 0),
+            Target::Fd(fd) => libc::fsetxattr(*fd, name.as_ptr(), data, value.len(), 0),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn raw_list(target: &Target, buf: *mut c_char, size: usize) -> ssize_t {
+    unsafe {
+        match target {
+            Target::Path(path, true) => libc::listxattr(path.as_ptr(), buf, size),
+            Target::Path(path, false) => libc::llistxattr(path.as_ptr(), buf, size),
+            Target::Fd(fd) => libc::flistxattr(*fd, buf, size),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn raw_remove(target: &Target, name: &CString) -> i32 {
+    unsafe {
+        match target {
+            Target::Path(path, true) => libc::removexattr(path.as_ptr(), name.as_ptr()),
+            Target::Path(path, false) => libc::lremovexattr(path.as_ptr(), name.as_ptr()),
+            Target::Fd(fd) => libc::fremovexattr(*fd, name.as_ptr()),
+        }
+    }
+}
+
+/*
+
+    Mac specific section
+
+ */
+#[cfg(target_os = "macos")]
+fn options(follow: bool) -> libc::c_int {
+    if follow { 0 } else { libc::XATTR_NOFOLLOW }
+}
+
+#[cfg(target_os = "macos")]
+fn raw_get(target: &Target, name: &CString, buf: *mut c_void, size: usize) -> ssize_t {
+    unsafe {
+        match target {
+            Target::Path(path, follow) => libc::getxattr(path.as_ptr(), name.as_ptr(), buf, size, 0, options(*follow)),
+            Target::Fd(fd) => libc::fgetxattr(*fd, name.as_ptr(), buf, size, 0, 0),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn raw_set(target: &Target, name: &CString, value: &[u8]) -> i32 {
+    let data = value.as_ptr() as *const c_void;
+    unsafe {
+        match target {
+            Target::Path(path, follow) => libc::setxattr(path.as_ptr(), name.as_ptr(), data, value.len(), 0, options(*follow)),
+            Target::Fd(fd) => libc::fsetxattr(*fd, name.as_ptr(), data, value.len(), 0, 0),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn raw_list(target: &Target, buf: *mut c_char, size: usize) -> ssize_t {
+    unsafe {
+        match target {
+            Target::Path(path, follow) => libc::listxattr(path.as_ptr(), buf, size, options(*follow)),
+            Target::Fd(fd) => libc::flistxattr(*fd, buf, size, 0),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn raw_remove(target: &Target, name: &CString) -> i32 {
+    unsafe {
+        match target {
+            Target::Path(path, follow) => libc::removexattr(path.as_ptr(), name.as_ptr(), options(*follow)),
+            Target::Fd(fd) => libc::fremovexattr(*fd, name.as_ptr(), 0),
+        }
+    }
+}
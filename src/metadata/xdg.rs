@@ -0,0 +1,500 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::metadata::xattr::{self, XattrError};
+
+/**
+    A freedesktop.org common extended attribute.
+    File managers and desktop search tools (such as Dolphin and Baloo) show these.
+
+    # Examples
+    ```rust
+    use system_extensions::metadata::xdg::XdgKey;
+
+    assert_eq!(XdgKey::OriginUrl.name(), "user.xdg.origin.url");
+    assert_eq!(XdgKey::from_name("user.baloo.rating"), Some(XdgKey::Rating));
+    ```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XdgKey {
+    /// `user.xdg.tags`: A comma separated list of tags.
+    Tags,
+    /// `user.xdg.comment`: A comment about the file.
+    Comment,
+    /// `user.xdg.origin.url`: The URL the file was downloaded from.
+    OriginUrl,
+    /// `user.xdg.referrer.url`: The page that linked to the downloaded file.
+    ReferrerUrl,
+    /// `user.xdg.language`: The language of the file's contents. (ex: `en_US`)
+    Language,
+    /// `user.baloo.rating`: A rating from 0 to 10, shown as 0 to 5 stars.
+    Rating,
+}
+
+/// Every key, in the order [`list`] returns them.
+const KEYS: [XdgKey; 6] = [XdgKey::Tags, XdgKey::Comment, XdgKey::OriginUrl, XdgKey::ReferrerUrl,
+    XdgKey::Language, XdgKey::Rating];
+
+impl XdgKey {
+    /**
+        Get the full name of the extended attribute.
+    */
+    pub fn name(self) -> &'static str {
+        match self {
+            XdgKey::Tags => "user.xdg.tags",
+            XdgKey::Comment => "user.xdg.comment",
+            XdgKey::OriginUrl => "user.xdg.origin.url",
+            XdgKey::ReferrerUrl => "user.xdg.referrer.url",
+            XdgKey::Language => "user.xdg.language",
+            XdgKey::Rating => "user.baloo.rating",
+        }
+    }
+
+    /**
+        Get the key of an extended attribute name, or None if it is not a common attribute.
+    */
+    pub fn from_name(name: &str) -> Option<XdgKey> {
+        KEYS.iter().copied().find(|key| key.name() == name)
+    }
+}
+
+impl fmt::Display for XdgKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/**
+    The value of a freedesktop.org common extended attribute.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum XdgAttribute {
+    /// The tags of the file.
+    Tags(Vec<String>),
+    /// A comment about the file.
+    Comment(String),
+    /// The URL the file was downloaded from.
+    OriginUrl(String),
+    /// The page that linked to the downloaded file.
+    ReferrerUrl(String),
+    /// The language of the file's contents.
+    Language(String),
+    /// The rating of the file, from 0 to 10.
+    Rating(u8),
+}
+
+impl XdgAttribute {
+    /**
+        Get the key the attribute is stored under.
+    */
+    pub fn key(&self) -> XdgKey {
+        match self {
+            XdgAttribute::Tags(_) => XdgKey::Tags,
+            XdgAttribute::Comment(_) => XdgKey::Comment,
+            XdgAttribute::OriginUrl(_) => XdgKey::OriginUrl,
+            XdgAttribute::ReferrerUrl(_) => XdgKey::ReferrerUrl,
+            XdgAttribute::Language(_) => XdgKey::Language,
+            XdgAttribute::Rating(_) => XdgKey::Rating,
+        }
+    }
+
+    /**
+        Parse the stored value of an attribute.
+
+        # Params
+        key: [`XdgKey`] -> The key the value is stored under. <br>
+        value: &[u8] -> The stored value.
+
+        # Returns
+        io::Result<XdgAttribute> -> The attribute, or an error of kind `InvalidData` if the value is not valid.
+    */
+    pub fn parse(key: XdgKey, value: &[u8]) -> io::Result<XdgAttribute> {
+        let text = std::str::from_utf8(value)
+            .map_err(|_| invalid_data(format!("{} is not valid UTF-8", key)))?
+            // Some tools store the value with a trailing null.
+            .trim_end_matches('\0');
+        Ok(match key {
+            XdgKey::Tags => XdgAttribute::Tags(split_tags(text)),
+            XdgKey::Comment => XdgAttribute::Comment(text.to_string()),
+            XdgKey::OriginUrl => XdgAttribute::OriginUrl(text.to_string()),
+            XdgKey::ReferrerUrl => XdgAttribute::ReferrerUrl(text.to_string()),
+            XdgKey::Language => XdgAttribute::Language(text.to_string()),
+            XdgKey::Rating => XdgAttribute::Rating(parse_rating(text)?),
+        })
+    }
+
+    /**
+        Get the value to store for the attribute.
+
+        # Returns
+        io::Result<Vec<u8>> -> The value, or an error of kind `InvalidInput` if a tag contains a comma
+        or the rating is above 10.
+    */
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        Ok(match self {
+            XdgAttribute::Tags(tags) => {
+                if let Some(tag) = tags.iter().find(|tag| tag.contains(',')) {
+                    return Err(invalid_input(format!("The tag '{}' contains a comma", tag)));
+                }
+                tags.join(",").into_bytes()
+            }
+            XdgAttribute::Comment(text) | XdgAttribute::OriginUrl(text)
+            | XdgAttribute::ReferrerUrl(text) | XdgAttribute::Language(text) => text.clone().into_bytes(),
+            XdgAttribute::Rating(rating) => {
+                if *rating > 10 {
+                    return Err(invalid_input(format!("The rating {} is above 10", rating)));
+                }
+                rating.to_string().into_bytes()
+            }
+        })
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn split_tags(text: &str) -> Vec<String> {
+    text.split(',').map(str::trim).filter(|tag| !tag.is_empty()).map(String::from).collect()
+}
+
+fn parse_rating(text: &str) -> io::Result<u8> {
+    match text.trim().parse::<u8>() {
+        Ok(rating) if rating <= 10 => Ok(rating),
+        _ => Err(invalid_data(format!("The rating '{}' is not a number from 0 to 10", text))),
+    }
+}
+
+/**
+   Get a common attribute of a file.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   key: [`XdgKey`] -> The attribute to get.
+
+   ## Returns
+   io::Result<Option<[`XdgAttribute`]>> -> The attribute, or None if the file does not have it.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::xdg::{get, XdgKey, XdgAttribute};
+   use std::path::Path;
+
+   if let Ok(Some(XdgAttribute::Comment(comment))) = get(Path::new("./notes.txt"), XdgKey::Comment) {
+       println!("{}", comment);
+   }
+   ```
+*/
+pub fn get(path: &Path, key: XdgKey) -> io::Result<Option<XdgAttribute>> {
+    match xattr::get(path, key.name()) {
+        Ok(value) => XdgAttribute::parse(key, &value).map(Some),
+        Err(XattrError::NoData) | Err(XattrError::NotSupported) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/**
+   Set a common attribute of a file, replacing the current value.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   attribute: &[`XdgAttribute`] -> The attribute to set.
+
+   ## Returns
+   io::Result<()> -> An error if the value is not valid or could not be stored.
+*/
+pub fn set(path: &Path, attribute: &XdgAttribute) -> io::Result<()> {
+    xattr::set(path, attribute.key().name(), &attribute.to_bytes()?).map_err(io::Error::from)
+}
+
+/**
+   Remove a common attribute from a file.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   key: [`XdgKey`] -> The attribute to remove.
+
+   ## Returns
+   io::Result<()> -> An error if the attribute could not be removed. Nothing happens if the file does not have it.
+*/
+pub fn remove(path: &Path, key: XdgKey) -> io::Result<()> {
+    match xattr::remove(path, key.name()) {
+        Ok(()) | Err(XattrError::NoData) | Err(XattrError::NotSupported) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/**
+   Get every common attribute a file has.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<Vec<[`XdgAttribute`]>> -> The attributes, in the order of [`XdgKey`].
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::xdg::list;
+   use std::path::Path;
+
+   for attribute in list(Path::new("./notes.txt")).unwrap_or_default() {
+       println!("{:?}", attribute);
+   }
+   ```
+*/
+pub fn list(path: &Path) -> io::Result<Vec<XdgAttribute>> {
+    let names = match xattr::list(path) {
+        Ok(names) => names,
+        Err(XattrError::NotSupported) => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut attributes = Vec::new();
+    for key in KEYS.iter().copied().filter(|key| names.iter().any(|name| name == key.name())) {
+        if let Some(attribute) = get(path, key)? {
+            attributes.push(attribute);
+        }
+    }
+    Ok(attributes)
+}
+
+/**
+   Get the tags of a file.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<Vec<String>> -> The tags, empty if the file has none.
+*/
+pub fn get_tags(path: &Path) -> io::Result<Vec<String>> {
+    match get(path, XdgKey::Tags)? {
+        Some(XdgAttribute::Tags(tags)) => Ok(tags),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/**
+   Set the tags of a file, replacing the current tags. An empty list removes the attribute.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   tags: &[S] -> The tags. (Tags cannot contain a comma.)
+
+   ## Returns
+   io::Result<()> -> An error if a tag is not valid or the tags could not be stored.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::xdg::set_tags;
+   use std::path::Path;
+
+   set_tags(Path::new("./holiday.jpg"), &["beach", "2021"]);
+   ```
+*/
+pub fn set_tags<S: AsRef<str>>(path: &Path, tags: &[S]) -> io::Result<()> {
+    let tags: Vec<String> = tags.iter().map(|tag| tag.as_ref().trim().to_string()).filter(|tag| !tag.is_empty()).collect();
+    if tags.is_empty() {
+        return remove(path, XdgKey::Tags);
+    }
+    set(path, &XdgAttribute::Tags(tags))
+}
+
+/**
+   Add a tag to a file. Nothing happens if the file already has the tag.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   tag: &str -> The tag to add.
+
+   ## Returns
+   io::Result<()> -> An error if the tag is not valid or could not be stored.
+*/
+pub fn add_tag(path: &Path, tag: &str) -> io::Result<()> {
+    let mut tags = get_tags(path)?;
+    if tags.iter().any(|t| t == tag.trim()) {
+        return Ok(());
+    }
+    tags.push(tag.to_string());
+    set_tags(path, &tags)
+}
+
+/**
+   Remove a tag from a file. Nothing happens if the file does not have the tag.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   tag: &str -> The tag to remove.
+
+   ## Returns
+   io::Result<()> -> An error if the tags could not be stored.
+*/
+pub fn remove_tag(path: &Path, tag: &str) -> io::Result<()> {
+    let tags = get_tags(path)?;
+    if !tags.iter().any(|t| t == tag.trim()) {
+        return Ok(());
+    }
+    set_tags(path, &tags.into_iter().filter(|t| t != tag.trim()).collect::<Vec<_>>())
+}
+
+/// Get a text attribute for the typed getters.
+fn get_text(path: &Path, key: XdgKey) -> io::Result<Option<String>> {
+    Ok(match get(path, key)? {
+        Some(XdgAttribute::Comment(text)) | Some(XdgAttribute::OriginUrl(text))
+        | Some(XdgAttribute::ReferrerUrl(text)) | Some(XdgAttribute::Language(text)) => Some(text),
+        _ => None,
+    })
+}
+
+/**
+   Get the comment of a file.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<Option<String>> -> The comment, or None if the file does not have one.
+*/
+pub fn get_comment(path: &Path) -> io::Result<Option<String>> {
+    get_text(path, XdgKey::Comment)
+}
+
+/**
+   Set the comment of a file.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   comment: &str -> The comment.
+
+   ## Returns
+   io::Result<()> -> An error if the comment could not be stored.
+*/
+pub fn set_comment(path: &Path, comment: &str) -> io::Result<()> {
+    set(path, &XdgAttribute::Comment(comment.to_string()))
+}
+
+/**
+   Get the URL a file was downloaded from.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<Option<String>> -> The URL, or None if the file does not have one.
+*/
+pub fn get_origin_url(path: &Path) -> io::Result<Option<String>> {
+    get_text(path, XdgKey::OriginUrl)
+}
+
+/**
+   Set the URL a file was downloaded from, the same as browsers do for downloads.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   url: &str -> The URL.
+
+   ## Returns
+   io::Result<()> -> An error if the URL could not be stored.
+
+   ## Examples
+   ```rust
+   use system_extensions::metadata::xdg::{set_origin_url, set_referrer_url};
+   use std::path::Path;
+
+   let path = Path::new("./Downloads/file.zip");
+   set_origin_url(path, "https://example.com/file.zip");
+   set_referrer_url(path, "https://example.com/downloads.html");
+   ```
+*/
+pub fn set_origin_url(path: &Path, url: &str) -> io::Result<()> {
+    set(path, &XdgAttribute::OriginUrl(url.to_string()))
+}
+
+/**
+   Get the page that linked to a downloaded file.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<Option<String>> -> The URL, or None if the file does not have one.
+*/
+pub fn get_referrer_url(path: &Path) -> io::Result<Option<String>> {
+    get_text(path, XdgKey::ReferrerUrl)
+}
+
+/**
+   Set the page that linked to a downloaded file.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   url: &str -> The URL.
+
+   ## Returns
+   io::Result<()> -> An error if the URL could not be stored.
+*/
+pub fn set_referrer_url(path: &Path, url: &str) -> io::Result<()> {
+    set(path, &XdgAttribute::ReferrerUrl(url.to_string()))
+}
+
+/**
+   Get the language of a file's contents.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<Option<String>> -> The language, or None if the file does not have one.
+*/
+pub fn get_language(path: &Path) -> io::Result<Option<String>> {
+    get_text(path, XdgKey::Language)
+}
+
+/**
+   Set the language of a file's contents.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   language: &str -> The language. (ex: `en_US`)
+
+   ## Returns
+   io::Result<()> -> An error if the language could not be stored.
+*/
+pub fn set_language(path: &Path, language: &str) -> io::Result<()> {
+    set(path, &XdgAttribute::Language(language.to_string()))
+}
+
+/**
+   Get the rating of a file.
+
+   ## Params
+   path: &Path -> The path to the file.
+
+   ## Returns
+   io::Result<Option<u8>> -> The rating from 0 to 10, or None if the file is not rated.
+*/
+pub fn get_rating(path: &Path) -> io::Result<Option<u8>> {
+    match get(path, XdgKey::Rating)? {
+        Some(XdgAttribute::Rating(rating)) => Ok(Some(rating)),
+        _ => Ok(None),
+    }
+}
+
+/**
+   Set the rating of a file. Each star is worth 2, so 7 is three and a half stars.
+
+   ## Params
+   path: &Path -> The path to the file. <br>
+   rating: u8 -> The rating from 0 to 10.
+
+   ## Returns
+   io::Result<()> -> An error of kind `InvalidInput` if the rating is above 10, or an error if it could not be stored.
+*/
+pub fn set_rating(path: &Path, rating: u8) -> io::Result<()> {
+    set(path, &XdgAttribute::Rating(rating))
+}
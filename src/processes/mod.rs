@@ -3,5 +3,13 @@
 */
 #[cfg(feature="processes")]
 pub mod processes;
+/**
+    Rank the processes on the system by their resource usage.
+    (Linux only)
+*/
+#[cfg(all(feature="processes", unix, not(target_os = "macos")))]
+pub mod monitor;
 #[cfg(test)]
-pub mod processes;
\ No newline at end of file
+pub mod processes;
+#[cfg(all(test, unix, not(target_os = "macos")))]
+pub mod monitor;
\ No newline at end of file
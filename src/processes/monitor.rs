@@ -0,0 +1,381 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/**
+    What the [`ProcessMonitor`] should rank the processes by.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortBy {
+    /// The CPU usage over the sampling interval. (100% is one full core.)
+    Cpu,
+    /// The resident set size.
+    Rss,
+    /// The proportional set size. (Requires permission to read `/proc/[pid]/smaps_rollup`.)
+    Pss,
+    /// The bytes read from and written to storage per second.
+    Io,
+}
+
+/**
+    A single process as sampled by the [`ProcessMonitor`].
+
+    Values that could not be read (usually due to permissions) are `None`.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcessSample {
+    /// The id of the process.
+    pub pid: u32,
+    /// The name of the process.
+    pub name: String,
+    /// The real user id of the process.
+    pub uid: u32,
+    /// The CPU usage over the sampling interval. (100% is one full core.)
+    pub cpu_percent: f64,
+    /// The resident set size in bytes.
+    pub rss: u64,
+    /// The proportional set size in bytes.
+    pub pss: Option<u64>,
+    /// The bytes read from and written to storage per second.
+    pub io_bytes_per_second: Option<f64>,
+}
+
+/**
+    A builder struct that samples all processes over an interval and ranks them,
+    similar to `top -b -n1`.
+
+    Ties are broken by the process id, so the output is deterministic.
+
+    # Examples
+    ```rust
+    use system_extensions::processes::monitor::{ProcessMonitor, SortBy};
+    use std::time::Duration;
+
+    let top = ProcessMonitor::new()
+        .set_sort_by(SortBy::Rss)
+        .set_limit(5)
+        .set_interval(Duration::from_millis(100))
+        .sample()
+        .unwrap();
+
+    for process in top {
+        println!("{} {} {}", process.pid, process.name, process.rss);
+    }
+    ```
+*/
+#[derive(Clone, Debug)]
+pub struct ProcessMonitor {
+    pub(crate) interval: Duration,
+    pub(crate) sort_by: SortBy,
+    pub(crate) limit: usize,
+    pub(crate) user: Option<User>,
+}
+
+/**
+    The user to filter the processes by.
+*/
+#[derive(Clone, Debug)]
+pub(crate) enum User {
+    Id(u32),
+    Name(String),
+}
+
+impl ProcessMonitor {
+    /**
+        Construct a new ProcessMonitor.
+
+        By default it samples for one second and returns the top 10 processes by CPU usage.
+
+        # Returns
+        The instance of a default ProcessMonitor.
+    */
+    pub fn new() -> ProcessMonitor {
+        ProcessMonitor {
+            interval: Duration::from_secs(1),
+            sort_by: SortBy::Cpu,
+            limit: 10,
+            user: None,
+        }
+    }
+
+    /**
+        Set how long the processes are sampled for.
+
+        # Params
+        interval: Duration -> The sampling interval.<br>
+
+        # Returns
+        A mutable instance of the ProcessMonitor.
+    */
+    pub fn set_interval(&mut self, interval: Duration) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /**
+        Set what the processes are ranked by.
+
+        # Params
+        sort_by: [`SortBy`] -> The value to rank by.<br>
+
+        # Returns
+        A mutable instance of the ProcessMonitor.
+    */
+    pub fn set_sort_by(&mut self, sort_by: SortBy) -> &mut Self {
+        self.sort_by = sort_by;
+        self
+    }
+
+    /**
+        Set the maximum amount of processes to return.
+
+        # Params
+        limit: usize -> The N in top N.<br>
+
+        # Returns
+        A mutable instance of the ProcessMonitor.
+    */
+    pub fn set_limit(&mut self, limit: usize) -> &mut Self {
+        self.limit = limit;
+        self
+    }
+
+    /**
+        Only include processes owned by a user.
+
+        # Params
+        uid: u32 -> The id of the user.<br>
+
+        # Returns
+        A mutable instance of the ProcessMonitor.
+    */
+    pub fn set_user(&mut self, uid: u32) -> &mut Self {
+        self.user = Some(User::Id(uid));
+        self
+    }
+
+    /**
+        Only include processes owned by a user, by the name of the user.
+        The name is looked up when sampling, which fails if the user does not exist.
+
+        # Params
+        name: &str -> The name of the user. (ex: root)<br>
+
+        # Returns
+        A mutable instance of the ProcessMonitor.
+    */
+    pub fn set_user_name(&mut self, name: &str) -> &mut Self {
+        self.user = Some(User::Name(name.to_string()));
+        self
+    }
+
+    /**
+        Sample the processes and return the top N.
+
+        This blocks for the sampling interval.
+
+        # Returns
+        Result<Vec<[`ProcessSample`]>, String> -> The ranked processes.
+    */
+    pub fn sample(&self) -> Result<Vec<ProcessSample>, String> {
+        let uid = match &self.user {
+            Some(User::Id(uid)) => Some(*uid),
+            Some(User::Name(name)) => Some(uid_by_name(name)?),
+            None => None,
+        };
+        let total_before = read_total_jiffies()?;
+        let before = snapshot(uid);
+        let start = Instant::now();
+        thread::sleep(self.interval);
+        let total_after = read_total_jiffies()?;
+        let after = snapshot(uid);
+        let elapsed = start.elapsed().as_secs_f64();
+
+        // Jiffies that passed per CPU, so 100% is one full core.
+        let (cpus, total) = total_after;
+        let wall_jiffies = total.saturating_sub(total_before.1) as f64 / cpus.max(1) as f64;
+
+        let mut samples: Vec<ProcessSample> = Vec::new();
+        for (pid, now) in after {
+            // Processes that started during the interval (including ones that reused a pid)
+            // used all of their CPU time and I/O during the interval.
+            let then = before.get(&pid).filter(|then| then.start_time == now.start_time);
+            let cpu_delta = now.cpu_jiffies.saturating_sub(then.map_or(0, |t| t.cpu_jiffies));
+            let cpu_percent = if wall_jiffies > 0.0 { cpu_delta as f64 / wall_jiffies * 100.0 } else { 0.0 };
+
+            let then_io = match then {
+                Some(then) => then.io_bytes,
+                None => Some(0),
+            };
+            let io_bytes_per_second = match (now.io_bytes, then_io) {
+                (Some(now_io), Some(then_io)) if elapsed > 0.0 => Some(now_io.saturating_sub(then_io) as f64 / elapsed),
+                _ => None,
+            };
+
+            let pss = if self.sort_by == SortBy::Pss { read_pss(pid) } else { None };
+
+            samples.push(ProcessSample {
+                pid,
+                name: now.name,
+                uid: now.uid,
+                cpu_percent,
+                rss: now.rss,
+                pss,
+                io_bytes_per_second,
+            });
+        }
+
+        let sort_by = self.sort_by;
+        samples.sort_by(|a, b| compare(sort_by, a, b));
+        samples.truncate(self.limit);
+
+        // PSS is expensive to read, so only read it for the processes that are returned.
+        if self.sort_by != SortBy::Pss {
+            for sample in samples.iter_mut() {
+                sample.pss = read_pss(sample.pid);
+            }
+        }
+
+        Ok(samples)
+    }
+}
+
+impl Default for ProcessMonitor {
+    fn default() -> Self {
+        ProcessMonitor::new()
+    }
+}
+
+/// Rank two samples from highest to lowest, falling back to the lowest pid.
+fn compare(sort_by: SortBy, a: &ProcessSample, b: &ProcessSample) -> Ordering {
+    let ordering = match sort_by {
+        SortBy::Cpu => b.cpu_percent.partial_cmp(&a.cpu_percent).unwrap_or(Ordering::Equal),
+        SortBy::Rss => b.rss.cmp(&a.rss),
+        SortBy::Pss => b.pss.cmp(&a.pss),
+        SortBy::Io => b.io_bytes_per_second.unwrap_or(-1.0)
+            .partial_cmp(&a.io_bytes_per_second.unwrap_or(-1.0)).unwrap_or(Ordering::Equal),
+    };
+    ordering.then(a.pid.cmp(&b.pid))
+}
+
+/// Read the current state of every process, or only the processes of a user.
+fn snapshot(uid: Option<u32>) -> HashMap<u32, RawProcess> {
+    let mut output = HashMap::new();
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return output
+    };
+    for entry in entries.flatten() {
+        let pid = match entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) {
+            Some(pid) => pid,
+            None => continue
+        };
+        // The process may have exited while walking /proc.
+        if let Some(process) = read_process(&entry.path()) {
+            if uid.is_none() || uid == Some(process.uid) {
+                output.insert(pid, process);
+            }
+        }
+    }
+    output
+}
+
+/// Get the id of a user by name, using the same lookup as `getent passwd`.
+fn uid_by_name(name: &str) -> Result<u32, String> {
+    let unknown = || format!("Unknown user: {}", name);
+    let c_name = std::ffi::CString::new(name).map_err(|_| unknown())?;
+    let mut buffer = vec![0 as libc::c_char; 1024];
+    loop {
+        let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        let code = unsafe {
+            libc::getpwnam_r(c_name.as_ptr(), &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        match code {
+            0 if result.is_null() => return Err(unknown()),
+            0 => return Ok(entry.pw_uid),
+            // The buffer was too small for the entry.
+            libc::ERANGE if buffer.len() < 1 << 20 => buffer.resize(buffer.len() * 2, 0),
+            code => return Err(format!("Unable to look up user {}: {}", name, std::io::Error::from_raw_os_error(code))),
+        }
+    }
+}
+
+/// The raw values of a process at a point in time.
+struct RawProcess {
+    name: String,
+    uid: u32,
+    /// When the process started, in jiffies since boot. Tells processes with a reused pid apart.
+    start_time: u64,
+    cpu_jiffies: u64,
+    rss: u64,
+    io_bytes: Option<u64>,
+}
+
+fn read_process(dir: &Path) -> Option<RawProcess> {
+    let stat = fs::read_to_string(dir.join("stat")).ok()?;
+    // The name is wrapped in parentheses and may contain spaces.
+    let name_end = stat.rfind(')')?;
+    let name = stat[stat.find('(')? + 1..name_end].to_string();
+    let fields: Vec<&str> = stat[name_end + 1..].split_whitespace().collect();
+    // utime and stime are fields 14 and 15, which are 11 and 12 after the name.
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    // starttime is field 22.
+    let start_time: u64 = fields.get(19)?.parse().ok()?;
+
+    let status = fs::read_to_string(dir.join("status")).ok()?;
+    let mut uid = 0;
+    let mut rss = 0;
+    for line in status.lines() {
+        if line.starts_with("Uid:") {
+            uid = line.split_whitespace().nth(1)?.parse().ok()?;
+        } else if line.starts_with("VmRSS:") {
+            rss = line.split_whitespace().nth(1)?.parse::<u64>().ok()? * 1024;
+        }
+    }
+
+    let io_bytes = fs::read_to_string(dir.join("io")).ok().map(|io| {
+        io.lines()
+            .filter(|l| l.starts_with("read_bytes:") || l.starts_with("write_bytes:"))
+            .filter_map(|l| l.split_whitespace().nth(1).and_then(|v| v.parse::<u64>().ok()))
+            .sum()
+    });
+
+    Some(RawProcess {
+        name,
+        uid,
+        start_time,
+        cpu_jiffies: utime + stime,
+        rss,
+        io_bytes,
+    })
+}
+
+fn read_pss(pid: u32) -> Option<u64> {
+    let rollup = fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("smaps_rollup")).ok()?;
+    rollup.lines()
+        .find(|l| l.starts_with("Pss:"))
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// Get the number of CPUs and the total jiffies spent by all of them.
+fn read_total_jiffies() -> Result<(usize, u64), String> {
+    let stat = fs::read_to_string("/proc/stat")
+        .map_err(|e| format!("Unable to read /proc/stat: {}", e))?;
+    let mut cpus = 0;
+    let mut total = 0;
+    for line in stat.lines() {
+        if line.starts_with("cpu ") {
+            total = line.split_whitespace().skip(1).filter_map(|v| v.parse::<u64>().ok()).sum();
+        } else if line.starts_with("cpu") {
+            cpus += 1;
+        }
+    }
+    Ok((cpus, total))
+}
@@ -173,6 +173,146 @@ pub fn find_process_id(process_name: &str) -> Result<u32, String> {
     Ok(result)
 }
 
+/**
+    System-wide process and load statistics.
+
+    Obtained using [`system_stats`] or [`system_stats_over`].
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemStats {
+    /// The load average over the last minute.
+    pub load_one: f64,
+    /// The load average over the last five minutes.
+    pub load_five: f64,
+    /// The load average over the last fifteen minutes.
+    pub load_fifteen: f64,
+    /// The total number of processes (and threads) on the system.
+    pub total_processes: u32,
+    /// The number of processes currently running.
+    pub running_processes: u32,
+    /// The number of processes blocked waiting for I/O.
+    pub blocked_processes: u32,
+    /// The number of context switches per second over the sampling interval.
+    pub context_switches_per_second: f64,
+    /// The number of forks since the system booted.
+    pub forks_since_boot: u64,
+    /// The maximum process id before ids wrap around.
+    pub pid_max: u32,
+}
+
+/**
+   Get the system-wide process and load statistics.
+
+   Context switches are sampled over a quarter of a second, so this call blocks
+   for that long. Use [`system_stats_over`] to choose the sampling interval.
+
+   # Returns
+   Result<[`SystemStats`], String> -> The statistics of the system.
+   # Examples
+   ```rust
+   use system_extensions::processes::processes;
+   let stats = processes::system_stats().unwrap();
+   println!("Load: {}", stats.load_one);
+   ```
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn system_stats() -> Result<SystemStats, String> {
+    system_stats_over(std::time::Duration::from_millis(250))
+}
+
+/**
+   Get the system-wide process and load statistics, sampling the context switches
+   over the provided interval.
+
+   # Params
+   interval: Duration -> How long to sample the context switches for.
+   # Returns
+   Result<[`SystemStats`], String> -> The statistics of the system.
+   # Examples
+   ```rust
+   use std::time::Duration;
+   use system_extensions::processes::processes;
+   let stats = processes::system_stats_over(Duration::from_secs(1)).unwrap();
+   println!("Context switches/s: {}", stats.context_switches_per_second);
+   ```
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn system_stats_over(interval: std::time::Duration) -> Result<SystemStats, String> {
+    use std::time::Instant;
+
+    let start = Instant::now();
+    let first = read_proc_stat()?;
+    std::thread::sleep(interval);
+    let second = read_proc_stat()?;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let loadavg = fs::read_to_string("/proc/loadavg")
+        .map_err(|e| format!("Unable to read /proc/loadavg: {}", e))?;
+    let mut fields = loadavg.split_whitespace();
+    let mut next_load = || -> Result<f64, String> {
+        fields.next().and_then(|v| v.parse::<f64>().ok())
+            .ok_or_else(|| String::from("Malformed /proc/loadavg."))
+    };
+    let load_one = next_load()?;
+    let load_five = next_load()?;
+    let load_fifteen = next_load()?;
+    let total_processes = loadavg.split_whitespace().nth(3)
+        .and_then(|v| v.split('/').nth(1))
+        .and_then(|v| v.parse::<u32>().ok())
+        .ok_or_else(|| String::from("Malformed /proc/loadavg."))?;
+
+    let pid_max = fs::read_to_string("/proc/sys/kernel/pid_max")
+        .map_err(|e| format!("Unable to read /proc/sys/kernel/pid_max: {}", e))?
+        .trim().parse::<u32>()
+        .map_err(|_| String::from("Malformed /proc/sys/kernel/pid_max."))?;
+
+    let switches = second.context_switches.saturating_sub(first.context_switches) as f64;
+    let context_switches_per_second = if elapsed > 0.0 { switches / elapsed } else { 0.0 };
+
+    Ok(SystemStats {
+        load_one,
+        load_five,
+        load_fifteen,
+        total_processes,
+        running_processes: second.procs_running,
+        blocked_processes: second.procs_blocked,
+        context_switches_per_second,
+        forks_since_boot: second.processes,
+        pid_max,
+    })
+}
+
+/// The values of /proc/stat used by [`system_stats_over`].
+#[cfg(all(unix, not(target_os = "macos")))]
+struct ProcStat {
+    context_switches: u64,
+    processes: u64,
+    procs_running: u32,
+    procs_blocked: u32,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_proc_stat() -> Result<ProcStat, String> {
+    let contents = fs::read_to_string("/proc/stat")
+        .map_err(|e| format!("Unable to read /proc/stat: {}", e))?;
+
+    let mut stat = ProcStat { context_switches: 0, processes: 0, procs_running: 0, procs_blocked: 0 };
+    for line in contents.lines() {
+        let mut parts = line.split_whitespace();
+        let key = parts.next().unwrap_or("");
+        let value = parts.next().unwrap_or("0");
+        match key {
+            "ctxt" => stat.context_switches = value.parse().unwrap_or(0),
+            "processes" => stat.processes = value.parse().unwrap_or(0),
+            "procs_running" => stat.procs_running = value.parse().unwrap_or(0),
+            "procs_blocked" => stat.procs_blocked = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    Ok(stat)
+}
+
 /*
 
     Mac OS Section